@@ -0,0 +1,211 @@
+//! State layout of the first deployed version of the app, read once by `DAOSignApp::migrate`.
+//!
+//! Legacy records are converted with the defaults of the fields added since. What the first
+//! version didn't record is left empty: the public keys and content hashes of signatures and
+//! the block height agreements were formed at. Attestations count as stored at the migration,
+//! the creation time their clients signed is often 0. The nfts minted back then are taken as
+//! minted under the id of their attestation, the first version didn't check its mints.
+//! Legacy signatures verify against the legacy messages only.
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    AccountId,
+};
+use std::collections::HashMap;
+
+use daosign_attestation::{Attestation, AttestationResult};
+use daosign_proof_of_agreement::{AgreementSignature, ProofOfAgreement};
+use daosign_proof_of_signature::ProofOfSignature;
+use daosign_schema::{
+    FieldPrivacy, PoaMintPolicy, PolicyOperator, Schema, SchemaDefinition, SchemaMetadata,
+    SignatoryPolicy,
+};
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyDAOSignApp {
+    pub schema_id: u128,
+    pub attestation_id: u128,
+    pub collections: HashMap<u128, AccountId>, // schema_id => nft collection contract
+    pub schemas: HashMap<u128, LegacySchema>,  // schema_id => Schema
+    pub attestations: HashMap<u128, LegacyAttestation>, // attestation_id => Attestation
+    pub poa: HashMap<u128, Vec<LegacyProofOfAgreement>>, // attestation_id => ProofOfAgreement
+    pub pos: HashMap<u128, Vec<LegacyProofOfSignature>>, // attestation_id => ProofOfSignature
+    pub signed_attestation: HashMap<u128, HashMap<String, bool>>, // attestation_id  => user => signed
+    pub user_attestation: HashMap<u128, HashMap<String, Vec<LegacyAttestation>>>, // schema_id  => user => Attestation[]
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacySchemaMetadata {
+    pub name: String,
+    pub description: String,
+    pub attestation_type: String,
+    pub nft_name: String,
+    pub nft_symbol: String,
+    pub collection_id: String,
+    pub creator: String,
+    pub created_at: u64,
+    pub is_nft: bool,
+    pub is_public: bool,
+    pub is_revokable: bool,
+    pub expire_in: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacySignatoryPolicy {
+    pub operator: u8,
+    pub signatory_description: String,
+    pub required_schema_id: Vec<u128>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacySchemaDefinition {
+    pub definition_type: String,
+    pub definition_name: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacySchema {
+    pub schema_id: u128,
+    pub metadata: LegacySchemaMetadata,
+    pub signatory_policy: Vec<LegacySignatoryPolicy>,
+    pub schema_definition: Vec<LegacySchemaDefinition>,
+    pub signature: Vec<u8>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacyAttestation {
+    pub attestation_id: u128,
+    pub schema_id: u128,
+    pub attestation_result: Vec<AttestationResult>,
+    pub creator: String,
+    pub recipient: String,
+    pub created_at: u64,
+    pub signatories: Vec<String>,
+    pub signature: Vec<u8>,
+    pub is_revoked: bool,
+    pub revoked_at: u64,
+    pub revoke_signature: Vec<u8>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacyProofOfSignature {
+    pub attestation_id: u128,
+    pub creator: String,
+    pub created_at: u64,
+    pub signature: Vec<u8>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct LegacyProofOfAgreement {
+    pub attestation_id: u128,
+    pub signatures: Vec<Vec<u8>>, // in the order of the proofs of signature
+}
+
+impl From<LegacySchema> for Schema {
+    fn from(s: LegacySchema) -> Self {
+        let m = s.metadata;
+        Schema {
+            schema_id: s.schema_id,
+            metadata: SchemaMetadata {
+                name: m.name,
+                description: m.description,
+                attestation_type: m.attestation_type,
+                nft_name: m.nft_name,
+                nft_symbol: m.nft_symbol,
+                collection_id: m.collection_id,
+                creator: m.creator,
+                created_at: m.created_at,
+                is_nft: m.is_nft,
+                is_public: m.is_public,
+                is_revokable: m.is_revokable,
+                expire_in: m.expire_in,
+                poa_mint_policy: PoaMintPolicy::App, // agreements were minted to the app
+            },
+            signatory_policy: s
+                .signatory_policy
+                .into_iter()
+                .map(|p| SignatoryPolicy {
+                    operator: PolicyOperator::try_from(p.operator).expect("invalid operator"),
+                    signatory_description: p.signatory_description,
+                    required_schema_id: p.required_schema_id,
+                    expression: None,
+                })
+                .collect(),
+            schema_definition: s
+                .schema_definition
+                .into_iter()
+                .map(|d| SchemaDefinition {
+                    definition_type: d.definition_type,
+                    definition_name: d.definition_name,
+                    privacy: FieldPrivacy::Plaintext,
+                })
+                .collect(),
+            signature: s.signature,
+        }
+    }
+}
+
+/// Attestation of `a`, stored at `stored_at` as the first version didn't record it.
+pub fn migrate_attestation(a: LegacyAttestation, stored_at: u64) -> Attestation {
+    Attestation {
+        attestation_id: a.attestation_id,
+        schema_id: a.schema_id,
+        attestation_result: a.attestation_result,
+        creator: a.creator,
+        recipient: a.recipient,
+        created_at: a.created_at,
+        signatories: a.signatories,
+        signature: a.signature,
+        is_revoked: a.is_revoked,
+        revoked_at: a.revoked_at,
+        revoke_signature: a.revoke_signature,
+        signatory_roles: vec![],
+        threshold: None,
+        signing_order: None,
+        sign_deadline: None,
+        is_cancelled: false,
+        cancelled_at: 0,
+        cancel_signature: vec![],
+        documents: vec![],
+        fields_root: vec![],
+        refers_to: None,
+        supersedes: None,
+        stored_at,
+    }
+}
+
+impl From<LegacyProofOfSignature> for ProofOfSignature {
+    fn from(pos: LegacyProofOfSignature) -> Self {
+        ProofOfSignature {
+            attestation_id: pos.attestation_id,
+            creator: pos.creator,
+            created_at: pos.created_at,
+            content_hash: vec![],
+            signed_at: pos.created_at,
+            public_key: vec![],
+            signature: pos.signature,
+        }
+    }
+}
+
+/// Agreement of `poa` with its signers taken from the proofs of signature `pos` it was formed from.
+pub fn migrate_poa(
+    poa: LegacyProofOfAgreement,
+    pos: &[LegacyProofOfSignature],
+) -> ProofOfAgreement {
+    ProofOfAgreement {
+        attestation_id: poa.attestation_id,
+        content_hash: vec![],
+        completed_at: 0,
+        signatures: poa
+            .signatures
+            .into_iter()
+            .zip(pos)
+            .map(|(signature, proof)| AgreementSignature {
+                account_id: proof.creator.clone(),
+                public_key: vec![],
+                signature,
+                signed_at: proof.created_at,
+            })
+            .collect(),
+    }
+}
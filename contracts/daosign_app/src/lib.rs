@@ -1,18 +1,67 @@
+mod legacy;
+
 mod daosign_app {
     use near_sdk::{
         borsh::{self, BorshDeserialize, BorshSerialize},
-        env, log, near_bindgen, serde_json, AccountId, Gas, NearToken, Promise,
+        env, ext_contract, log, near_bindgen, serde_json, AccountId, Gas, NearToken, Promise,
+        PromiseError, PromiseResult,
     };
     use serde::{Deserialize, Serialize};
     use serde_json::json;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
-    use daosign_attestation::Attestation;
-    use daosign_proof_of_agreement::ProofOfAgreement;
-    use daosign_proof_of_signature::ProofOfSignature;
-    use daosign_schema::Schema;
+    use daosign_attestation::{
+        disclosure::FieldDisclosure, Attestation, AttestationStatus, BulkAttestation, SignatoryRole,
+    };
+    use daosign_proof_of_agreement::{AgreementSignature, ProofOfAgreement};
+    use daosign_proof_of_signature::{AttestationRecords, ProofOfRejection, ProofOfSignature};
+    use daosign_schema::{PoaMintPolicy, Schema, SignatoryPolicy};
 
     use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
+
+    use crate::legacy::{migrate_attestation, migrate_poa, LegacyDAOSignApp};
+
+    const DEPLOY_COLLECTION_GAS: Gas = Gas::from_tgas(100); // create, deploy & init the collection
+    const CALLBACK_GAS: Gas = Gas::from_tgas(15);
+    const NEAR_PER_STORAGE: NearToken = NearToken::from_yoctonear(10u128.pow(19)); // 10e19yⓃ
+    const MINT_STORAGE_OVERHEAD: u128 = 400; // bytes of token owner, enumeration & keys besides the mint args
+    const MINT_BASE_TGAS: u64 = 10;
+    const BURN_GAS: Gas = Gas::from_tgas(10);
+    const ONE_YOCTO: NearToken = NearToken::from_yoctonear(1);
+    // Batches are bounded so that validating every item stays within the 300 Tgas of a transaction,
+    // full batches should attach 300 Tgas
+    pub const MAX_BATCH: usize = 20;
+    // Gas the nft mints scheduled by a batch may reserve, the rest of the 300 Tgas validates and
+    // stores the items
+    pub const MAX_BATCH_MINT_GAS: Gas = Gas::from_tgas(200);
+    const STORAGE_ENTRY_OVERHEAD: u128 = 40; // bytes of the map key & length prefix of a stored record
+
+    /// State of the nft minted for an attestation.
+    #[derive(
+        BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq,
+    )]
+    pub enum NftStatus {
+        Pending,
+        Minted,
+        Failed,
+        Burned,
+    }
+
+    /// Token to be minted in an nft collection.
+    #[derive(
+        BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq,
+    )]
+    pub struct NftMint {
+        pub token_id: String,
+        pub token_owner_id: AccountId,
+    }
+
+    /// Interface of the `daosign_factory` contract.
+    #[ext_contract(ext_factory)]
+    pub trait DaoSignFactory {
+        fn deploy_nft(&mut self, schema_id: u128, nft_acc: String) -> bool;
+    }
+
     /// Main storage structure for DAOsignApp contract.
     #[near_bindgen]
     #[derive(
@@ -22,30 +71,60 @@ mod daosign_app {
         // Counters for schemas and attestations
         pub schema_id: u128,
         pub attestation_id: u128,
+        pub bulk_id: u128,
+
+        // Factory that deploys nft collections for schemas
+        pub factory_id: Option<AccountId>,
 
         // nft collection hashmap
         pub collections: HashMap<u128, AccountId>, // schema_id => nft collection contract
+        pub pending_collections: HashMap<u128, AccountId>, // schema_id => nft collection awaiting deployment
         // Mappings
         pub schemas: HashMap<u128, Schema>, // schema_id => Schema
         pub attestations: HashMap<u128, Attestation>, // attestation_id => Attestation
-        pub poa: HashMap<u128, Vec<ProofOfAgreement>>, // attestation_id => ProofOfSignature
-        pub pos: HashMap<u128, Vec<ProofOfSignature>>, // attestation_id => ProofOfAgreement
-        pub signed_attestation: HashMap<u128, HashMap<String, bool>>, // attestation_id  => user => signed
+        pub poa: HashMap<u128, ProofOfAgreement>, // attestation_id => ProofOfAgreement
+        pub pos: HashMap<u128, Vec<ProofOfSignature>>, // attestation_id => ProofOfSignature
+        pub signed_attestation: HashMap<u128, HashSet<AccountId>>, // attestation_id  => signers
+        pub rejections: HashMap<u128, Vec<ProofOfRejection>>, // attestation_id => ProofOfRejection
         pub user_attestation: HashMap<u128, HashMap<String, Vec<Attestation>>>, // schema_id  => user => Attestation[]
+        pub bulk_attestations: HashMap<u128, BulkAttestation>, // bulk_id => BulkAttestation
+        pub bulk_claims: HashMap<u128, HashMap<String, u128>>, // bulk_id => hex leaf hash => attestation_id
+        pub document_index: HashMap<String, Vec<u128>>, // hex document hash => attestation_id[]
+        pub references: HashMap<u128, Vec<u128>>, // attestation_id => attestations referring to it
+        pub amendments: HashMap<u128, Vec<u128>>, // attestation_id => proposed amendments
+        pub superseded_by: HashMap<u128, u128>,   // attestation_id => agreed amendment
+
+        // nft mints
+        pub nft_status: HashMap<u128, NftStatus>, // attestation_id => NftStatus
+        pub pending_mints: HashMap<u128, Vec<NftMint>>, // attestation_id => mints not confirmed yet
+        pub nft_tokens: HashMap<u128, Vec<NftMint>>, // attestation_id => minted tokens
     }
 
     impl Default for DAOSignApp {
         fn default() -> Self {
             Self {
+                factory_id: None,
                 collections: HashMap::new(),
+                pending_collections: HashMap::new(),
                 schema_id: 0,
                 attestation_id: 0,
+                bulk_id: 0,
                 schemas: HashMap::new(),
                 attestations: HashMap::new(),
                 poa: HashMap::new(),
                 pos: HashMap::new(),
                 signed_attestation: HashMap::new(),
+                rejections: HashMap::new(),
                 user_attestation: HashMap::new(),
+                bulk_attestations: HashMap::new(),
+                bulk_claims: HashMap::new(),
+                document_index: HashMap::new(),
+                references: HashMap::new(),
+                amendments: HashMap::new(),
+                superseded_by: HashMap::new(),
+                nft_status: HashMap::new(),
+                pending_mints: HashMap::new(),
+                nft_tokens: HashMap::new(),
             }
         }
     }
@@ -56,27 +135,157 @@ mod daosign_app {
         ///
         /// This constructor initializes a new DAOsignApp contract instance.
         ///
+        /// # Arguments
+        ///
+        /// * `factory_id` - Account of the `daosign_factory` contract used to deploy nft collections.
+        ///
         /// # Returns
         ///
         /// A new instance of DAOsignApp.
         #[init]
-        pub fn new() -> Self {
+        pub fn new(factory_id: Option<AccountId>) -> Self {
             Self {
+                factory_id,
                 collections: HashMap::new(),
+                pending_collections: HashMap::new(),
                 schema_id: 0,
                 attestation_id: 0,
+                bulk_id: 0,
                 schemas: HashMap::new(),
                 attestations: HashMap::new(),
                 poa: HashMap::new(),
                 pos: HashMap::new(),
                 signed_attestation: HashMap::new(),
+                rejections: HashMap::new(),
                 user_attestation: HashMap::new(),
+                bulk_attestations: HashMap::new(),
+                bulk_claims: HashMap::new(),
+                document_index: HashMap::new(),
+                references: HashMap::new(),
+                amendments: HashMap::new(),
+                superseded_by: HashMap::new(),
+                nft_status: HashMap::new(),
+                pending_mints: HashMap::new(),
+                nft_tokens: HashMap::new(),
+            }
+        }
+
+        /// # Migration from the state of the first deployed version.
+        ///
+        /// Converts the legacy records to the current layout, see the `legacy` module for what
+        /// couldn't be recovered. Legacy collections stay active and the nfts minted through them
+        /// can be burned on revoke.
+        ///
+        /// # Arguments
+        ///
+        /// * `factory_id` - Account of the `daosign_factory` contract used to deploy nft collections.
+        ///
+        /// # Returns
+        ///
+        /// The migrated DAOsignApp.
+        #[private]
+        #[init(ignore_state)]
+        pub fn migrate(factory_id: Option<AccountId>) -> Self {
+            let old: LegacyDAOSignApp = env::state_read().expect("legacy state not found!");
+            let mut app = Self::new(factory_id);
+            let now = env::block_timestamp();
+
+            app.schema_id = old.schema_id;
+            app.attestation_id = old.attestation_id;
+            app.collections = old.collections;
+            app.schemas = old
+                .schemas
+                .into_iter()
+                .map(|(id, s)| (id, s.into()))
+                .collect();
+            app.attestations = old
+                .attestations
+                .into_iter()
+                .map(|(id, a)| (id, migrate_attestation(a, now)))
+                .collect();
+            app.user_attestation = old
+                .user_attestation
+                .into_iter()
+                .map(|(schema_id, users)| {
+                    let users = users
+                        .into_iter()
+                        .map(|(user, list)| {
+                            let list = list
+                                .into_iter()
+                                .map(|a| migrate_attestation(a, now))
+                                .collect();
+                            (user, list)
+                        })
+                        .collect();
+                    (schema_id, users)
+                })
+                .collect();
+            app.signed_attestation = old
+                .signed_attestation
+                .into_iter()
+                .map(|(id, users)| {
+                    let signers = users
+                        .into_iter()
+                        .filter(|(_, signed)| *signed)
+                        .filter_map(|(user, _)| user.parse().ok())
+                        .collect();
+                    (id, signers)
+                })
+                .collect();
+            for (id, agreements) in old.poa {
+                let pos = old.pos.get(&id).cloned().unwrap_or_default();
+                if let Some(poa) = agreements.into_iter().next() {
+                    app.poa.insert(id, migrate_poa(poa, &pos));
+                }
+            }
+            app.pos = old
+                .pos
+                .into_iter()
+                .map(|(id, proofs)| (id, proofs.into_iter().map(Into::into).collect()))
+                .collect();
+
+            // The first version minted one token per attestation, named after it: to the recipient
+            // when stored without signatory policy, to the app once agreed. Revokes burned it.
+            for (id, a) in &app.attestations {
+                let Some(s) = app.schemas.get(&a.schema_id) else {
+                    continue;
+                };
+                if a.is_revoked || !s.metadata.is_nft || !app.collections.contains_key(&a.schema_id)
+                {
+                    continue;
+                }
+                let owner = if s.signatory_policy.is_empty() && !a.recipient.is_empty() {
+                    a.recipient.parse().expect("Invalid address")
+                } else if app.poa.contains_key(id) {
+                    env::current_account_id()
+                } else {
+                    continue;
+                };
+                app.nft_tokens.insert(
+                    *id,
+                    vec![NftMint {
+                        token_id: id.to_string(),
+                        token_owner_id: owner,
+                    }],
+                );
+                app.nft_status.insert(*id, NftStatus::Minted);
             }
+
+            log!(
+                "Event: Migrated {{ schemas: {}, attestations: {} }}",
+                app.schemas.len(),
+                app.attestations.len()
+            );
+            app
         }
 
         /// # Message to store a Schema.
         ///
         /// This function stores a Schema and validates the message. If the data is valid, it is stored in the contract.
+        /// For nft schemas a fresh collection is deployed through the factory with the attached deposit,
+        /// and the schema becomes active once `on_collection_deployed` confirms the deployment. The app names
+        /// the collection after the schema id, see `get_collection_account`, so creators of nft schemas sign
+        /// an empty `collection_id`. Schemas without nft don't need a deposit, it is refunded.
         ///
         /// # Arguments
         ///
@@ -88,20 +297,24 @@ mod daosign_app {
             data.validate();
 
             data.schema_id = self.schema_id;
-            // Store the schema
-            self.schemas.insert(self.schema_id, data.clone());
 
             if data.metadata.is_nft {
-                self.collections.insert(
-                    data.schema_id,
-                    data.metadata
-                        .collection_id
-                        .clone()
-                        .parse()
-                        .expect("invalid id"),
+                // The collection is deployed by the factory and owned by the app
+                assert!(
+                    data.metadata.collection_id.is_empty(),
+                    "invalid collection account!"
                 );
+                self.deploy_collection(data.schema_id, self.get_collection_account(data.schema_id));
+            } else {
+                let deposit = env::attached_deposit();
+                if !deposit.is_zero() {
+                    Promise::new(env::predecessor_account_id()).transfer(deposit);
+                }
             }
 
+            // Store the schema
+            self.schemas.insert(self.schema_id, data.clone());
+
             // Increment schema ID
             self.schema_id += 1;
 
@@ -109,62 +322,359 @@ mod daosign_app {
             log!("Event: SchemaCreated {{ data: {:?} }}", data.clone());
         }
 
+        /// # Message to deploy again the collection of an nft schema whose deployment failed.
+        ///
+        /// Only the schema creator can retry, with a deposit covering the deployment like in `store_schema`.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - Schema whose collection failed to deploy.
+        #[payable]
+        pub fn retry_collection_deployment(&mut self, schema_id: u128) {
+            let s = self.get_schema(schema_id);
+            assert!(
+                s.metadata.creator == env::signer_account_id().as_str(),
+                "unauthorized schema creator!"
+            );
+            assert!(s.metadata.is_nft, "schema has no collection!");
+            assert!(
+                !self.collections.contains_key(&schema_id),
+                "collection already deployed!"
+            );
+            assert!(
+                !self.pending_collections.contains_key(&schema_id),
+                "collection deployment pending!"
+            );
+
+            self.deploy_collection(schema_id, self.get_collection_account(schema_id));
+        }
+
+        /// # Util method to deploy the collection of an nft schema through the factory.
+        ///
+        /// The attached deposit pays for the deployment, `on_collection_deployed` resolves it.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - Schema the collection is deployed for.
+        /// * `collection` - Account of the collection.
+        fn deploy_collection(&mut self, schema_id: u128, collection: AccountId) {
+            let factory = self
+                .factory_id
+                .clone()
+                .expect("nft factory not configured!");
+            self.pending_collections
+                .insert(schema_id, collection.clone());
+
+            let deposit = env::attached_deposit();
+            ext_factory::ext(factory)
+                .with_attached_deposit(deposit)
+                .with_static_gas(DEPLOY_COLLECTION_GAS)
+                .deploy_nft(schema_id, collection.to_string())
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(CALLBACK_GAS)
+                        .on_collection_deployed(
+                            schema_id,
+                            collection,
+                            env::predecessor_account_id(),
+                            deposit,
+                        ),
+                );
+        }
+
+        /// # Message to retrieve the account of the nft collection of a schema.
+        ///
+        /// The collection is a subaccount of the factory only the app can deploy.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - ID of the Schema.
+        pub fn get_collection_account(&self, schema_id: u128) -> AccountId {
+            let factory = self
+                .factory_id
+                .clone()
+                .expect("nft factory not configured!");
+            format!("schema-{}.{}", schema_id, factory)
+                .parse()
+                .expect("invalid id")
+        }
+
+        /// # Callback resolving the collection deployment of an nft schema.
+        ///
+        /// Activates the schema if the factory deployed its collection, otherwise refunds the deposit
+        /// to the account that stored the schema, which stays inactive.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - Schema the collection was deployed for.
+        /// * `collection` - Account of the collection.
+        /// * `payer` - Account that attached the deployment deposit.
+        /// * `deposit` - Deposit forwarded to the factory.
+        #[private]
+        pub fn on_collection_deployed(
+            &mut self,
+            schema_id: u128,
+            collection: AccountId,
+            payer: AccountId,
+            deposit: NearToken,
+            #[callback_result] deployed: Result<bool, PromiseError>,
+        ) -> bool {
+            self.pending_collections.remove(&schema_id);
+
+            if !matches!(deployed, Ok(true)) {
+                // The factory refunds the app, pass the deposit back to the payer
+                if !deposit.is_zero() {
+                    Promise::new(payer.clone()).transfer(deposit);
+                }
+
+                log!(
+                    "Event: SchemaCollectionFailed {{ schema_id: {}, collection: {}, refund_to: {} }}",
+                    schema_id,
+                    collection,
+                    payer
+                );
+                return false;
+            }
+
+            self.collections.insert(schema_id, collection.clone());
+
+            log!(
+                "Event: SchemaActivated {{ schema_id: {}, collection: {} }}",
+                schema_id,
+                collection
+            );
+            true
+        }
+
         /// # Message to store a Attestation.
         ///
         /// This function stores a Attestation and validates the message. If the data is valid, it is stored in the contract.
+        /// The attached deposit must cover its storage and nft mint, the excess is refunded.
         ///
         /// # Arguments
         ///
         /// * `data` - Attestation struct containing the schema data.
         /// * `caller` - Address of user who sign this message.
         #[payable]
-        pub fn store_attestation(&mut self, mut data: Attestation) {
-            static ZERO_ID: String = String::new(); // Define zero address
+        pub fn store_attestation(&mut self, data: Attestation) {
+            let (_, cost) = self.store_attestation_internal(data);
+            self.charge_deposit(cost);
+        }
+
+        /// # Util method to validate and store an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - Attestation to store.
+        ///
+        /// # Returns
+        ///
+        /// The id of the stored Attestation and the deposit its storage and nft mint cost.
+        fn store_attestation_internal(&mut self, data: Attestation) -> (u128, NearToken) {
+            assert!(
+                data.supersedes.is_none(),
+                "amendments go through amend_attestation!"
+            );
 
             let s = self.get_schema(data.schema_id);
+            assert!(
+                self.is_schema_active(data.schema_id),
+                "schema is not active!"
+            );
 
             // Validate the data
             data.validate(s.clone());
 
-            data.attestation_id = self.attestation_id;
+            self.insert_attestation(&s, data)
+        }
 
-            // Store attestation
-            self.attestations.insert(self.attestation_id, data.clone());
+        /// # Message to store several Attestations at once.
+        ///
+        /// Every Attestation is validated and stored like in `store_attestation`, a single invalid item
+        /// fails the whole batch. The attached deposit must cover the storage and nft mints of the whole batch,
+        /// the excess is refunded.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - Attestations to store, at most `MAX_BATCH`, and whose nft mints reserve at most
+        ///   `MAX_BATCH_MINT_GAS`.
+        ///
+        /// # Returns
+        ///
+        /// The ids of the stored Attestations, in the order of `data`.
+        #[payable]
+        pub fn store_attestations_batch(&mut self, data: Vec<Attestation>) -> Vec<u128> {
+            assert_batch(data.len());
+            assert_batch_mint_gas(
+                data.iter()
+                    .map(|a| self.attestation_mint_gas(a))
+                    .fold(Gas::from_gas(0), Gas::saturating_add),
+            );
 
-            if s.metadata.is_nft && s.signatory_policy.len() == 0 && data.recipient != ZERO_ID {
-                let contract_address = self
-                    .collections
-                    .get(&data.schema_id)
-                    .expect("collection not exist");
+            let mut ids = Vec::with_capacity(data.len());
+            let mut cost = NearToken::from_yoctonear(0);
+            for a in data {
+                let (id, a_cost) = self.store_attestation_internal(a);
+                ids.push(id);
+                cost = cost.saturating_add(a_cost);
+            }
+            self.charge_deposit(cost);
 
-                // Call the deployed contract's `nft_mint` method
-                let args = json!({
-                    "token_id": data.attestation_id.to_string(),
-                    "token_owner_id": data.recipient,
-                    "token_metadata":daosign_metadata()
-                })
-                .to_string()
-                .into_bytes();
+            log!("Event: AttestationBatchStored {{ ids: {:?} }}", ids);
+            ids
+        }
 
-                Promise::new(contract_address.clone()).function_call(
-                    String::from("nft_mint"),
-                    args,
-                    NearToken::from_yoctonear(5_250_000_000_000_000_000_000),
-                    Gas::from_tgas(5),
-                );
-            }
-            // Store attestation for user / signatories
-            if data.recipient != ZERO_ID {
-                let recipient: Vec<String> = vec![data.clone().recipient]; // Wrap the recipient in a Vec
-                self.store_user_attestation(recipient, data.clone());
-            }
-            self.store_user_attestation(data.signatories.clone(), data.clone());
+        /// # Message to store a Bulk Attestation.
+        ///
+        /// This function stores the signed Merkle root of many Attestations of a schema. The Attestations
+        /// themselves are not stored until their holders claim them with `claim_attestation`. The attached
+        /// deposit must cover the storage of the root and its metadata, the excess is refunded.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - BulkAttestation struct containing the root and its signature.
+        ///
+        /// # Returns
+        ///
+        /// The id of the stored Bulk Attestation.
+        #[payable]
+        pub fn store_bulk_attestation(&mut self, mut data: BulkAttestation) -> u128 {
+            let s = self.get_schema(data.schema_id);
+            assert!(
+                self.is_schema_active(data.schema_id),
+                "schema is not active!"
+            );
 
-            //Increment schema id
-            self.attestation_id += 1;
+            // Validate the data
+            data.validate(&s);
+
+            data.bulk_id = self.bulk_id;
+            self.bulk_attestations.insert(data.bulk_id, data.clone());
+            self.bulk_id += 1;
+            self.charge_deposit(storage_cost(bulk_storage_bytes(&data)));
+
+            //Emit event
+            log!("Event: BulkAttestationCreated {{ data: {:?} }}", data);
+            data.bulk_id
+        }
+
+        /// # Message to claim an Attestation of a Bulk Attestation.
+        ///
+        /// This function stores an Attestation once its inclusion under the root is proven. It is then
+        /// registered for its recipient and signatories, and its nft is minted, like a stored Attestation.
+        /// The creator signed the root rather than the Attestation, so it is stored without a signature
+        /// and verified through the Bulk Attestation, see `get_claimed_attestation_id`.
+        ///
+        /// # Arguments
+        ///
+        /// * `bulk_id` - Bulk Attestation the Attestation belongs to.
+        /// * `data` - Attestation as hashed into the Merkle tree.
+        /// * `proof` - Sibling hashes from the leaf of the Attestation up to the root.
+        ///
+        /// # Returns
+        ///
+        /// The id of the stored Attestation.
+        #[payable]
+        pub fn claim_attestation(
+            &mut self,
+            bulk_id: u128,
+            mut data: Attestation,
+            proof: Vec<Vec<u8>>,
+        ) -> u128 {
+            let caller = env::signer_account_id().to_string();
+            assert!(
+                data.recipient == caller || data.signatories.contains(&caller),
+                "unauthorized claim!"
+            );
+            assert!(
+                data.supersedes.is_none(),
+                "amendments go through amend_attestation!"
+            );
+            assert!(
+                self.verify_bulk_inclusion(bulk_id, data.clone(), proof),
+                "invalid inclusion proof!"
+            );
+
+            let leaf = hex_key(&data.leaf_hash());
+            assert!(
+                !self
+                    .bulk_claims
+                    .get(&bulk_id)
+                    .is_some_and(|claims| claims.contains_key(&leaf)),
+                "attestation already claimed!"
+            );
+
+            let s = self.get_schema(data.schema_id);
+            assert!(
+                self.is_schema_active(data.schema_id),
+                "schema is not active!"
+            );
+            data.validate_content(&s);
+
+            // Whatever signature the claimer passed is not the creator's
+            data.signature.clear();
+            let (attestation_id, cost) = self.insert_attestation(&s, data);
+            self.charge_deposit(cost);
+            self.bulk_claims
+                .entry(bulk_id)
+                .or_default()
+                .insert(leaf, attestation_id);
+
+            //Emit event
+            log!(
+                "Event: AttestationClaimed {{ bulk_id: {}, attestation_id: {} }}",
+                bulk_id,
+                attestation_id
+            );
+            attestation_id
+        }
+
+        /// # Message to amend an Attestation.
+        ///
+        /// This function stores an amendment of an Attestation, validated like `store_attestation`. The
+        /// amendment has the signatories, roles and signing rules of the original and supersedes it once
+        /// its Proof of Agreement is formed.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - Attestation with `supersedes` set to the id of the amended Attestation.
+        ///
+        /// # Returns
+        ///
+        /// The id of the amendment.
+        #[payable]
+        pub fn amend_attestation(&mut self, data: Attestation) -> u128 {
+            let original_id = data.supersedes.expect("invalid amendment!");
+            let original = self.get_attestation(original_id);
+            assert!(
+                !self.superseded_by.contains_key(&original_id),
+                "attestation already superseded!"
+            );
+            data.validate_amendment(&original);
+
+            let s = self.get_schema(data.schema_id);
+            assert!(
+                self.is_schema_active(data.schema_id),
+                "schema is not active!"
+            );
+
+            // Validate the data
+            data.validate(s.clone());
+
+            let (attestation_id, cost) = self.insert_attestation(&s, data);
+            self.charge_deposit(cost);
+            self.amendments
+                .entry(original_id)
+                .or_default()
+                .push(attestation_id);
 
             //Emit event
-            log!("Event: AttestationCreated {{ data: {:?} }}", data);
+            log!(
+                "Event: AttestationAmendmentProposed {{ attestation_id: {}, amendment_id: {} }}",
+                original_id,
+                attestation_id
+            );
+            attestation_id
         }
 
         /// # Message to store a Revoke.
@@ -188,21 +698,16 @@ mod daosign_app {
                 let contract_address = self
                     .collections
                     .get(&a.schema_id)
-                    .expect("collection not exist");
-
-                // Call the deployed contract's `nft_mint` method
-                let args = json!({
-                    "token_id": a.attestation_id.to_string(),
-                })
-                .to_string()
-                .into_bytes();
-
-                Promise::new(contract_address.clone()).function_call(
-                    String::from("nft_burn"),
-                    args,
-                    NearToken::from_yoctonear(5_250_000_000_000_000_000_000),
-                    Gas::from_tgas(5),
-                );
+                    .expect("collection not exist")
+                    .clone();
+
+                // Tokens still pending are burned once `on_nft_minted` confirms them
+                let tokens = self
+                    .nft_tokens
+                    .get(&a.attestation_id)
+                    .cloned()
+                    .unwrap_or_default();
+                self.burn_nfts(a.attestation_id, contract_address, tokens);
             }
 
             // modify store revoke status
@@ -230,12 +735,64 @@ mod daosign_app {
             self.attestations.insert(a.attestation_id, a.clone());
 
             //Emit event
-            log!("Event: Revoked {{ attestation: {:?} }}", a);
+            log!("Event: Revoked {{ attestation: {:?} }}", a.redacted(&s));
+        }
+
+        /// # Message to cancel an Attestation.
+        ///
+        /// This function lets the creator withdraw an Attestation awaiting signatures before its Proof of Agreement
+        /// is formed. The nft already minted for it is burned.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation id that will be cancelled.
+        /// * `signature` - Signature of the cancel message by the creator.
+        pub fn cancel_attestation(&mut self, attestation_id: u128, signature: Vec<u8>) {
+            let mut a = self.get_attestation(attestation_id);
+
+            a.validate_cancel(signature.clone());
+            assert!(
+                !self.poa.contains_key(&attestation_id),
+                "agreement already completed!"
+            );
+
+            a.is_cancelled = true;
+            a.cancelled_at = env::block_timestamp();
+            a.cancel_signature = signature;
+
+            if let Some(contract_address) = self.collections.get(&a.schema_id).cloned() {
+                let tokens = self
+                    .nft_tokens
+                    .get(&attestation_id)
+                    .cloned()
+                    .unwrap_or_default();
+                self.burn_nfts(attestation_id, contract_address, tokens);
+            }
+
+            // Refresh the copies kept per user
+            if let Some(user_attestations) = self.user_attestation.get_mut(&a.schema_id) {
+                for attestation in user_attestations.values_mut().flatten() {
+                    if attestation.attestation_id == attestation_id {
+                        *attestation = a.clone();
+                    }
+                }
+            }
+
+            self.attestations.insert(attestation_id, a.clone());
+
+            //Emit event
+            let s = self.get_schema(a.schema_id);
+            log!(
+                "Event: AttestationCancelled {{ attestation: {:?} }}",
+                a.redacted(&s)
+            );
         }
 
         /// # Message to store a Proof of Signature.
         ///
         /// This function stores a Proof of Signature and validates the message. If the data is valid, it is stored in the contract.
+        /// The attached deposit must cover its storage and the nft mints of the agreement it completes, the excess
+        /// is refunded.
         ///
         /// # Arguments
         ///
@@ -243,20 +800,64 @@ mod daosign_app {
         /// * `caller` - Address of user who sign this message.
         #[payable]
         pub fn store_pos(&mut self, data: ProofOfSignature) {
+            let cost = self.store_pos_internal(data);
+            self.charge_deposit(cost);
+        }
+
+        /// # Util method to validate and store a Proof of Signature.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - Proof of Signature to store.
+        ///
+        /// # Returns
+        ///
+        /// The deposit its storage and the nft mints of the completed agreement cost.
+        fn store_pos_internal(&mut self, mut data: ProofOfSignature) -> NearToken {
             let caller_id = env::signer_account_id();
-            if let Some(true) = self
+            if self
                 .signed_attestation
                 .get(&data.attestation_id)
-                .and_then(|map| map.get(&String::from(caller_id.as_str())))
+                .map_or(false, |signers| signers.contains(&caller_id))
             {
                 panic!("Attestation already signed by caller.");
             }
+            assert!(
+                !self
+                    .get_rejecters(data.attestation_id)
+                    .contains(&String::from(caller_id.as_str())),
+                "Attestation already rejected by caller."
+            );
 
             let a = self.get_attestation(data.attestation_id);
+            assert!(
+                a.is_threshold_reachable(&self.get_rejecters(data.attestation_id)),
+                "attestation rejected."
+            );
+            // Neither a replaced version nor a competing amendment of it can be signed
+            assert!(
+                !self.superseded_by.contains_key(&a.attestation_id)
+                    && !a
+                        .supersedes
+                        .is_some_and(|id| self.superseded_by.contains_key(&id)),
+                "attestation already superseded."
+            );
             let s = self.get_schema(a.schema_id);
 
             // Validate the data
-            data.validate(a.clone(), s, &self.user_attestation);
+            let records = AttestationRecords {
+                user_attestation: &self.user_attestation,
+                attestations: &self.attestations,
+                schemas: &self.schemas,
+                superseded_by: &self.superseded_by,
+            };
+            let signed = self.get_signers(data.attestation_id);
+            let rejected = self.get_rejecters(data.attestation_id);
+            data.validate(a.clone(), s, &records, &signed, &rejected);
+
+            // Record when the signature reached the chain and the key it was made with
+            data.created_at = env::block_timestamp();
+            data.public_key = env::signer_account_pk().as_bytes()[1..].to_vec();
 
             // Store the ProofOfSignature
             self.pos
@@ -264,75 +865,550 @@ mod daosign_app {
                 .or_insert_with(Vec::new)
                 .push(data.clone());
 
-            let proofs = self.get_proof_of_signature(a.attestation_id);
-
-            if a.signatories.clone().len() == proofs.len() {
-                self.store_poa(a)
-            }
-
             // Mark the attestation as signed by the caller
             self.signed_attestation
                 .entry(data.attestation_id)
-                .or_insert_with(HashMap::new)
-                .insert(String::from(caller_id.clone().as_str()), true);
+                .or_insert_with(HashSet::new)
+                .insert(caller_id.clone());
+
+            // Signatures after the threshold is reached are recorded without a new PoA
+            let mut cost = storage_cost(pos_storage_bytes(&data));
+            let signed = self.get_signers(a.attestation_id);
+            if !self.poa.contains_key(&a.attestation_id) && a.is_threshold_reached(&signed) {
+                cost = cost.saturating_add(self.store_poa(a));
+            }
 
             log!(
                 "Event: ProofOfSignatureStored {{pos: {:?} }} ",
                 data.clone()
             );
+            cost
         }
 
-        /// # Message to store a Proof of Agreement.
+        /// # Message to store several Proofs of Signature of the caller at once.
         ///
-        /// This function stores a Proof of Agreement . If the data is valid, it is stored in the contract.
+        /// Every Proof of Signature is validated and stored like in `store_pos`, a single invalid item
+        /// fails the whole batch. The attached deposit must cover the storage and nft mints of the whole batch,
+        /// the excess is refunded.
         ///
         /// # Arguments
         ///
-        /// * `a` - Attestation that contains all needed information to get Proof of Signature.
-        fn store_poa(&mut self, a: Attestation) {
-            let proofs = self.get_proof_of_signature(a.attestation_id);
-
-            // Create a vector to store the extracted signatures
-            let mut signatory_proofs: Vec<Vec<u8>> = Vec::new();
+        /// * `data` - Proofs of Signature to store, at most `MAX_BATCH`, and whose nft mints reserve at
+        ///   most `MAX_BATCH_MINT_GAS`.
+        ///
+        /// # Returns
+        ///
+        /// The ids of the signed Attestations, in the order of `data`.
+        #[payable]
+        pub fn store_pos_batch(&mut self, data: Vec<ProofOfSignature>) -> Vec<u128> {
+            assert_batch(data.len());
+            assert_batch_mint_gas(
+                data.iter()
+                    .map(|pos| self.pos_mint_gas(pos))
+                    .fold(Gas::from_gas(0), Gas::saturating_add),
+            );
 
-            // Loop through the proofs and extract the signatures
-            for proof in &proofs {
-                signatory_proofs.push(proof.signature.clone());
+            let mut ids = Vec::with_capacity(data.len());
+            let mut cost = NearToken::from_yoctonear(0);
+            for pos in data {
+                ids.push(pos.attestation_id);
+                cost = cost.saturating_add(self.store_pos_internal(pos));
             }
-            let contract_address = self
-                .collections
-                .get(&a.schema_id)
-                .expect("collection not exist");
-
-            // Call the deployed contract's `nft_mint` method
-            let args = json!({
-                "token_id": a.attestation_id.to_string(),
-                "token_owner_id": env::current_account_id(),
-                "token_metadata":daosign_metadata()
-            })
-            .to_string()
-            .into_bytes();
+            self.charge_deposit(cost);
 
-            Promise::new(contract_address.clone()).function_call(
-                String::from("nft_mint"),
-                args,
-                NearToken::from_yoctonear(5_250_000_000_000_000_000_000),
-                Gas::from_tgas(5),
+            log!(
+                "Event: ProofOfSignatureBatchStored {{ attestation_ids: {:?} }}",
+                ids
             );
+            ids
+        }
 
-            // Create the ProofOfAgreement struct
-            let proof_of_agreement = ProofOfAgreement {
+        /// # Util method to estimate the gas of the nft mint scheduled when an Attestation is stored.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - Attestation to store.
+        fn attestation_mint_gas(&self, data: &Attestation) -> Gas {
+            let s = self.get_schema(data.schema_id);
+            let mints =
+                s.metadata.is_nft && s.signatory_policy.is_empty() && !data.recipient.is_empty();
+            mint_gas(mints as usize)
+        }
+
+        /// # Util method to estimate the gas of the nft mints scheduled when a Proof of Signature is stored.
+        ///
+        /// Assumes the worst case, the proof completes the agreement.
+        ///
+        /// # Arguments
+        ///
+        /// * `data` - Proof of Signature to store.
+        fn pos_mint_gas(&self, data: &ProofOfSignature) -> Gas {
+            let a = self.get_attestation(data.attestation_id);
+            let s = self.get_schema(a.schema_id);
+            if !s.metadata.is_nft
+                || !self.collections.contains_key(&a.schema_id)
+                || self.poa.contains_key(&a.attestation_id)
+            {
+                return Gas::from_gas(0);
+            }
+            mint_gas(poa_mints(&a, &s.metadata.poa_mint_policy).len())
+        }
+
+        /// # Util method to check the attached deposit covers what the call stored and minted.
+        ///
+        /// Refunds the deposit exceeding the `cost`.
+        ///
+        /// # Arguments
+        ///
+        /// * `cost` - Storage and nft mint cost of the call.
+        fn charge_deposit(&self, cost: NearToken) {
+            let deposit = env::attached_deposit();
+            assert!(deposit >= cost, "insufficient storage deposit!");
+
+            let excess = deposit.saturating_sub(cost);
+            if !excess.is_zero() {
+                Promise::new(env::predecessor_account_id()).transfer(excess);
+            }
+        }
+
+        /// # Message to reject an Attestation.
+        ///
+        /// This function stores a signatory's signed refusal to sign an Attestation. The Attestation
+        /// becomes rejected once the remaining signatories can't reach its signing threshold.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation id that will be rejected.
+        /// * `content_hash` - `Attestation::content_hash` of the rejected Attestation.
+        /// * `reason_hash` - sha256 hash of the reason kept off-chain.
+        /// * `signature` - Signature of the rejection message by the signatory.
+        pub fn store_rejection(
+            &mut self,
+            attestation_id: u128,
+            content_hash: Vec<u8>,
+            reason_hash: Vec<u8>,
+            signature: Vec<u8>,
+        ) {
+            let caller_id = String::from(env::signer_account_id().as_str());
+            assert!(
+                !self.get_signers(attestation_id).contains(&caller_id),
+                "Attestation already signed by caller."
+            );
+            assert!(
+                !self.get_rejecters(attestation_id).contains(&caller_id),
+                "Attestation already rejected by caller."
+            );
+            assert!(
+                !self.poa.contains_key(&attestation_id),
+                "agreement already completed!"
+            );
+
+            let a = self.get_attestation(attestation_id);
+            let data = ProofOfRejection {
+                attestation_id,
+                creator: caller_id,
+                content_hash,
+                reason_hash,
+                created_at: env::block_timestamp(),
+                signature,
+            };
+
+            // Validate the data
+            data.validate(a.clone());
+
+            self.rejections
+                .entry(attestation_id)
+                .or_insert_with(Vec::new)
+                .push(data.clone());
+
+            log!("Event: AttestationRejectedBy {{rejection: {:?} }} ", data);
+
+            if !a.is_threshold_reachable(&self.get_rejecters(attestation_id)) {
+                log!(
+                    "Event: AttestationRejected {{ attestation_id: {} }}",
+                    attestation_id
+                );
+            }
+        }
+
+        /// # Message to store a Proof of Agreement.
+        ///
+        /// This function stores a Proof of Agreement . If the data is valid, it is stored in the contract.
+        /// The agreement nft is minted only for nft schemas with a registered collection.
+        ///
+        /// # Arguments
+        ///
+        /// * `a` - Attestation that contains all needed information to get Proof of Signature.
+        ///
+        /// # Returns
+        ///
+        /// The deposit attached to the nft mints.
+        fn store_poa(&mut self, a: Attestation) -> NearToken {
+            let proofs = self.get_proof_of_signature(a.attestation_id);
+
+            // Collect the signatures with everything needed to verify them
+            let signatures: Vec<AgreementSignature> = proofs
+                .iter()
+                .map(|proof| AgreementSignature {
+                    account_id: proof.creator.clone(),
+                    public_key: proof.public_key.clone(),
+                    signature: proof.signature.clone(),
+                    signed_at: proof.signed_at,
+                })
+                .collect();
+
+            // Only nft schemas with a deployed collection mint the agreement
+            let mut cost = NearToken::from_yoctonear(0);
+            let s = self.get_schema(a.schema_id);
+            if s.metadata.is_nft {
+                if let Some(contract_address) = self.collections.get(&a.schema_id).cloned() {
+                    let mints = poa_mints(&a, &s.metadata.poa_mint_policy);
+                    cost = self.mint_nfts(a.attestation_id, contract_address, mints);
+                }
+            }
+
+            // Create the ProofOfAgreement struct
+            let proof_of_agreement = ProofOfAgreement {
                 attestation_id: a.attestation_id,
-                signatures: signatory_proofs, // Store the collected signatures
+                content_hash: a.content_hash(),
+                completed_at: env::block_height(),
+                signatures,
             };
             self.poa
-                .entry(a.attestation_id)
-                .or_insert_with(Vec::new)
-                .push(proof_of_agreement.clone());
+                .insert(a.attestation_id, proof_of_agreement.clone());
             log!(
                 "Event: ProofOfAgreementStored {{poa: {:?} }} ",
                 proof_of_agreement.clone()
             );
+
+            // An agreed amendment takes effect
+            if let Some(original_id) = a.supersedes {
+                self.superseded_by.insert(original_id, a.attestation_id);
+                log!(
+                    "Event: AttestationSuperseded {{ attestation_id: {}, superseded_by: {} }}",
+                    original_id,
+                    a.attestation_id
+                );
+            }
+            cost
+        }
+
+        /// # Message to retry the failed nft mints of an Attestation.
+        ///
+        /// Mints again the tokens whose `nft_mint` call failed. Only the attestation creator or recipient can retry.
+        /// The attached deposit must cover the nft mints, the excess is refunded.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation whose nft mint failed.
+        #[payable]
+        pub fn retry_nft_mint(&mut self, attestation_id: u128) {
+            let a = self.get_attestation(attestation_id);
+            let caller_id = env::signer_account_id();
+            assert!(
+                a.creator == caller_id || a.recipient == caller_id,
+                "unauthorized nft mint retry!"
+            );
+            assert!(!a.is_revoked, "attestation revoked!");
+            assert!(!a.is_cancelled, "attestation cancelled!");
+            assert!(
+                self.nft_status.get(&attestation_id) == Some(&NftStatus::Failed),
+                "nft mint is not failed!"
+            );
+
+            let contract_address = self
+                .collections
+                .get(&a.schema_id)
+                .expect("collection not exist")
+                .clone();
+            let mints = self
+                .pending_mints
+                .get(&attestation_id)
+                .cloned()
+                .unwrap_or_default();
+
+            let cost = self.mint_nfts(attestation_id, contract_address, mints);
+            self.charge_deposit(cost);
+        }
+
+        /// # Callback resolving the `nft_mint` calls of an Attestation.
+        ///
+        /// Marks the nft as minted once every mint succeeded, otherwise keeps the failed mints for `retry_nft_mint`.
+        /// Tokens minted for an attestation revoked or cancelled in the meantime are burned.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation the tokens were minted for.
+        /// * `mints` - Minted tokens, in the order of the joined promises.
+        #[private]
+        pub fn on_nft_minted(&mut self, attestation_id: u128, mints: Vec<NftMint>) -> bool {
+            let mut minted: Vec<NftMint> = Vec::new();
+            let mut failed: Vec<NftMint> = Vec::new();
+            for (i, mint) in mints.into_iter().enumerate() {
+                if matches!(env::promise_result(i as u64), PromiseResult::Successful(_)) {
+                    minted.push(mint);
+                } else {
+                    failed.push(mint);
+                }
+            }
+
+            // Failed mints stay pending for a retry
+            let pending = self.pending_mints.entry(attestation_id).or_default();
+            pending.retain(|mint| !minted.contains(mint));
+            let is_pending = !pending.is_empty();
+            if !is_pending {
+                self.pending_mints.remove(&attestation_id);
+            }
+
+            if !minted.is_empty() {
+                log!(
+                    "Event: NftMinted {{ attestation_id: {}, mints: {:?} }}",
+                    attestation_id,
+                    minted
+                );
+                self.nft_tokens
+                    .entry(attestation_id)
+                    .or_default()
+                    .extend(minted.clone());
+
+                // The revoke or cancel found no token to burn while the mint was pending
+                let a = self.get_attestation(attestation_id);
+                if a.is_revoked || a.is_cancelled {
+                    if let Some(contract_address) = self.collections.get(&a.schema_id).cloned() {
+                        self.burn_nfts(attestation_id, contract_address, minted);
+                    }
+                }
+            }
+
+            if !failed.is_empty() {
+                log!(
+                    "Event: NftMintFailed {{ attestation_id: {}, mints: {:?} }}",
+                    attestation_id,
+                    failed
+                );
+                self.nft_status.insert(attestation_id, NftStatus::Failed);
+                return false;
+            }
+
+            if !is_pending {
+                self.nft_status.insert(attestation_id, NftStatus::Minted);
+            }
+            true
+        }
+
+        /// # Callback resolving the `nft_burn` calls of a revoked Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation whose tokens were burned.
+        /// * `tokens` - Burned tokens, in the order of the joined promises.
+        #[private]
+        pub fn on_nft_burned(&mut self, attestation_id: u128, tokens: Vec<NftMint>) -> bool {
+            let mut burned: Vec<NftMint> = Vec::new();
+            for (i, token) in tokens.into_iter().enumerate() {
+                if matches!(env::promise_result(i as u64), PromiseResult::Successful(_)) {
+                    burned.push(token);
+                }
+            }
+
+            let remaining = self.nft_tokens.entry(attestation_id).or_default();
+            remaining.retain(|token| !burned.contains(token));
+            if !remaining.is_empty() {
+                log!(
+                    "Event: NftBurnFailed {{ attestation_id: {}, tokens: {:?} }}",
+                    attestation_id,
+                    remaining
+                );
+                return false;
+            }
+
+            self.nft_tokens.remove(&attestation_id);
+            self.nft_status.insert(attestation_id, NftStatus::Burned);
+            log!("Event: NftBurned {{ attestation_id: {} }}", attestation_id);
+            true
+        }
+
+        /// # Util method to mint nfts for an Attestation.
+        ///
+        /// Calls `nft_mint` for every token with deposit and gas sized by the mint arguments,
+        /// and resolves all of them in a single `on_nft_minted` callback.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation the tokens are minted for.
+        /// * `contract_address` - nft collection contract.
+        /// * `mints` - Tokens to mint.
+        ///
+        /// # Returns
+        ///
+        /// The deposit attached to the mints.
+        fn mint_nfts(
+            &mut self,
+            attestation_id: u128,
+            contract_address: AccountId,
+            mints: Vec<NftMint>,
+        ) -> NearToken {
+            let mut promises: Vec<Promise> = Vec::new();
+            let mut cost = NearToken::from_yoctonear(0);
+            for mint in &mints {
+                // Call the deployed contract's `nft_mint` method
+                let args = json!({
+                    "token_id": mint.token_id,
+                    "token_owner_id": mint.token_owner_id,
+                    "token_metadata": daosign_metadata()
+                })
+                .to_string()
+                .into_bytes();
+
+                let (deposit, gas) = mint_cost(args.len());
+                cost = cost.saturating_add(deposit);
+                promises.push(Promise::new(contract_address.clone()).function_call(
+                    String::from("nft_mint"),
+                    args,
+                    deposit,
+                    gas,
+                ));
+            }
+
+            let Some(mint_promise) = promises.into_iter().reduce(|acc, p| acc.and(p)) else {
+                return cost;
+            };
+
+            self.nft_status.insert(attestation_id, NftStatus::Pending);
+            let pending = self.pending_mints.entry(attestation_id).or_default();
+            for mint in &mints {
+                if !pending.contains(mint) {
+                    pending.push(mint.clone());
+                }
+            }
+
+            mint_promise.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(CALLBACK_GAS)
+                    .on_nft_minted(attestation_id, mints),
+            );
+            cost
+        }
+
+        /// # Util method to burn the nfts of an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - Attestation whose tokens are burned.
+        /// * `contract_address` - nft collection contract.
+        /// * `tokens` - Minted tokens to burn.
+        fn burn_nfts(
+            &mut self,
+            attestation_id: u128,
+            contract_address: AccountId,
+            tokens: Vec<NftMint>,
+        ) {
+            let mut promises: Vec<Promise> = Vec::new();
+            for token in &tokens {
+                // Call the deployed contract's `nft_burn` method
+                let args = json!({
+                    "token_id": token.token_id,
+                })
+                .to_string()
+                .into_bytes();
+
+                promises.push(Promise::new(contract_address.clone()).function_call(
+                    String::from("nft_burn"),
+                    args,
+                    ONE_YOCTO,
+                    BURN_GAS,
+                ));
+            }
+
+            let Some(burn_promise) = promises.into_iter().reduce(|acc, p| acc.and(p)) else {
+                return;
+            };
+
+            burn_promise.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(CALLBACK_GAS)
+                    .on_nft_burned(attestation_id, tokens),
+            );
+        }
+
+        /// # Util method to store a validated Attestation.
+        ///
+        /// Assigns the next attestation id, stores the Attestation for its recipient and signatories
+        /// and mints its nft when the schema has one.
+        ///
+        /// # Arguments
+        ///
+        /// * `s` - Schema of the Attestation.
+        /// * `data` - Validated Attestation.
+        ///
+        /// # Returns
+        ///
+        /// The id of the stored Attestation and the deposit its storage and nft mint cost.
+        fn insert_attestation(&mut self, s: &Schema, mut data: Attestation) -> (u128, NearToken) {
+            static ZERO_ID: String = String::new(); // Define zero address
+
+            if let Some(referred) = data.refers_to {
+                assert!(
+                    self.attestations.contains_key(&referred),
+                    "referred attestation not found!"
+                );
+            }
+
+            data.attestation_id = self.attestation_id;
+            data.stored_at = env::block_timestamp();
+            let mut cost = storage_cost(attestation_storage_bytes(&data));
+
+            // Store attestation
+            self.attestations.insert(self.attestation_id, data.clone());
+
+            if s.metadata.is_nft && s.signatory_policy.len() == 0 && data.recipient != ZERO_ID {
+                let contract_address = self
+                    .collections
+                    .get(&data.schema_id)
+                    .expect("collection not exist")
+                    .clone();
+
+                let mint = NftMint {
+                    token_id: data.attestation_id.to_string(),
+                    token_owner_id: data.recipient.parse().expect("Invalid address"),
+                };
+                cost = cost.saturating_add(self.mint_nfts(
+                    data.attestation_id,
+                    contract_address,
+                    vec![mint],
+                ));
+            }
+            // Store attestation for user / signatories
+            if data.recipient != ZERO_ID {
+                let recipient: Vec<String> = vec![data.clone().recipient]; // Wrap the recipient in a Vec
+                self.store_user_attestation(recipient, data.clone());
+            }
+            self.store_user_attestation(data.signatories.clone(), data.clone());
+
+            if let Some(referred) = data.refers_to {
+                self.references
+                    .entry(referred)
+                    .or_default()
+                    .push(data.attestation_id);
+            }
+
+            // Index the attestation by the hashes of its documents
+            for document in &data.documents {
+                self.document_index
+                    .entry(hex_key(&document.hash))
+                    .or_default()
+                    .push(data.attestation_id);
+            }
+
+            //Increment schema id
+            self.attestation_id += 1;
+
+            //Emit event
+            log!(
+                "Event: AttestationCreated {{ data: {:?} }}",
+                data.redacted(s)
+            );
+            (data.attestation_id, cost)
         }
 
         /// # Util method to store user attestation.
@@ -371,6 +1447,20 @@ mod daosign_app {
             self.schemas.get(&schema_id).unwrap().clone()
         }
 
+        /// # Message to check whether a Schema accepts attestations.
+        ///
+        /// A schema is active once it is stored and, for nft schemas, its collection is deployed.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - String representing the ID of the Schema.
+        pub fn is_schema_active(&self, schema_id: u128) -> bool {
+            match self.schemas.get(&schema_id) {
+                Some(s) => !s.metadata.is_nft || self.collections.contains_key(&schema_id),
+                None => false,
+            }
+        }
+
         /// # Message to retrieve a Attestation by its attestation id id.
         ///
         /// This function retrieves a stored Attestation by its id.
@@ -382,77 +1472,455 @@ mod daosign_app {
             self.attestations.get(&attestation_id).unwrap().clone()
         }
 
-        /// # Message to retrieve a Proof of Signature by attestation id.
+        /// # Message to retrieve a Bulk Attestation by its bulk id.
         ///
-        /// This function retrieves a stored Proof of Signature by  attestation id.
+        /// # Arguments
+        ///
+        /// * `bulk_id` - ID of the Bulk Attestation.
+        pub fn get_bulk_attestation(&self, bulk_id: u128) -> BulkAttestation {
+            self.bulk_attestations
+                .get(&bulk_id)
+                .expect("bulk attestation not found!")
+                .clone()
+        }
+
+        /// # Message to check that an Attestation is part of a Bulk Attestation.
         ///
         /// # Arguments
         ///
-        /// * `attestation_id` - String representing the ID of the Attestation.
-        pub fn get_proof_of_signature(&self, attestation_id: u128) -> Vec<ProofOfSignature> {
-            self.pos.get(&attestation_id).unwrap().clone()
+        /// * `bulk_id` - ID of the Bulk Attestation.
+        /// * `data` - Attestation as hashed into the Merkle tree.
+        /// * `proof` - Sibling hashes from the leaf of the Attestation up to the root.
+        pub fn verify_bulk_inclusion(
+            &self,
+            bulk_id: u128,
+            data: Attestation,
+            proof: Vec<Vec<u8>>,
+        ) -> bool {
+            self.get_bulk_attestation(bulk_id).includes(&data, &proof)
         }
 
-        /// # Message to retrieve a Proof of Agreement by attestation id.
+        /// # Message to retrieve the id of a claimed Attestation of a Bulk Attestation.
         ///
-        /// This function retrieves a stored Proof of Agreement by  attestation id.
+        /// # Arguments
+        ///
+        /// * `bulk_id` - ID of the Bulk Attestation.
+        /// * `data` - Attestation as hashed into the Merkle tree.
+        pub fn get_claimed_attestation_id(&self, bulk_id: u128, data: Attestation) -> Option<u128> {
+            self.bulk_claims
+                .get(&bulk_id)
+                .and_then(|claims| claims.get(&hex_key(&data.leaf_hash())))
+                .copied()
+        }
+
+        /// # Message to check a value revealed off-chain against its commitment.
         ///
         /// # Arguments
         ///
-        /// * `attestation_id` - String representing the ID of the Attestation.
-        pub fn get_proof_of_agreement(&self, attestation_id: u128) -> Vec<ProofOfAgreement> {
-            self.poa.get(&attestation_id).unwrap().clone()
+        /// * `attestation_id` - ID of the Attestation.
+        /// * `name` - Name of a `HashCommitment` field of the schema.
+        /// * `salt` - Salt of the commitment.
+        /// * `value` - Revealed value.
+        pub fn verify_field_reveal(
+            &self,
+            attestation_id: u128,
+            name: String,
+            salt: Vec<u8>,
+            value: Vec<u8>,
+        ) -> bool {
+            let a = self.get_attestation(attestation_id);
+            a.verify_field_reveal(&self.get_schema(a.schema_id), &name, &salt, &value)
         }
 
-        /// # Message to retrieve an Attestations  for a specific user by schema id & his address.
+        /// # Message to check a field disclosed by the holder of an Attestation.
         ///
-        /// This function retrieves a stored Attestation by  by schema id & user address.
+        /// A revoked, cancelled or superseded Attestation discloses nothing, its proofs are rejected.
         ///
         /// # Arguments
         ///
-        /// * `schema_id` - String representing the ID of the Schema.
-        /// * `caller` - Address of a specific user.
-        pub fn get_user_attestations(&self, schema_id: u128, caller: String) -> Vec<Attestation> {
-            self.user_attestation
-                .get(&schema_id) // Get the map for schema_id
-                .and_then(|caller_map| caller_map.get(&caller)) // Get the attestations for caller
-                .cloned() // Clone the vector 
-                .unwrap_or_else(Vec::new) // If None, return an empty Vec
+        /// * `attestation_id` - ID of the Attestation.
+        /// * `field_name` - Name of the disclosed field.
+        /// * `value` - Disclosed value.
+        /// * `salt` - Salt of the field.
+        /// * `proof` - Sibling hashes from the leaf of the field up to the `fields_root` of the Attestation.
+        pub fn verify_field_disclosure(
+            &self,
+            attestation_id: u128,
+            field_name: String,
+            value: Vec<u8>,
+            salt: Vec<u8>,
+            proof: Vec<Vec<u8>>,
+        ) -> bool {
+            let a = self.get_attestation(attestation_id);
+            if a.is_revoked || a.is_cancelled || self.superseded_by.contains_key(&attestation_id) {
+                return false;
+            }
+            a.verify_field_disclosure(&FieldDisclosure {
+                name: field_name,
+                value,
+                salt,
+                proof,
+            })
         }
-    }
-    pub fn daosign_metadata() -> TokenMetadata {
-        TokenMetadata {
-            title: Some("Olympus Mons".into()),
-            description: Some("The tallest mountain in the charted solar system".into()),
-            media: None,
-            media_hash: None,
-            copies: Some(1u64),
-            issued_at: None,
-            expires_at: None,
-            starts_at: None,
-            updated_at: None,
-            extra: None,
-            reference: None,
-            reference_hash: None,
+
+        /// # Message to retrieve the amendments proposed for an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - ID of the amended Attestation.
+        pub fn get_amendments(&self, attestation_id: u128) -> Vec<u128> {
+            self.amendments
+                .get(&attestation_id)
+                .cloned()
+                .unwrap_or_default()
         }
-    }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        /// # Message to retrieve the agreed amendment replacing an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - ID of the Attestation.
+        pub fn get_superseded_by(&self, attestation_id: u128) -> Option<u128> {
+            self.superseded_by.get(&attestation_id).copied()
+        }
+
+        /// # Message to retrieve the amendment chain of an Attestation.
+        ///
+        /// Returns the versions of the Attestation from the original to the version in effect. A pending
+        /// amendment is listed after the versions it amends, see `get_amendments` for the others.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - ID of any version of the Attestation.
+        pub fn get_amendment_chain(&self, attestation_id: u128) -> Vec<Attestation> {
+            // Amendments always have a greater id than what they supersede, so both walks end
+            let mut chain = vec![self.get_attestation(attestation_id)];
+            while let Some(previous) = chain[0].supersedes {
+                chain.insert(0, self.get_attestation(previous));
+            }
+            while let Some(next) = self.get_superseded_by(chain[chain.len() - 1].attestation_id) {
+                chain.push(self.get_attestation(next));
+            }
+            chain
+        }
+
+        /// # Message to retrieve the Attestations referring to an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - ID of the referred Attestation.
+        pub fn get_referring_attestations(&self, attestation_id: u128) -> Vec<u128> {
+            self.references
+                .get(&attestation_id)
+                .cloned()
+                .unwrap_or_default()
+        }
+
+        /// # Message to retrieve the Attestations anchoring a document.
+        ///
+        /// # Arguments
+        ///
+        /// * `hash` - Hash of the document, as in the `documents` of the Attestations.
+        pub fn find_attestations_by_document_hash(&self, hash: Vec<u8>) -> Vec<Attestation> {
+            self.document_index
+                .get(&hex_key(&hash))
+                .map(|ids| ids.iter().map(|id| self.get_attestation(*id)).collect())
+                .unwrap_or_default()
+        }
+
+        /// # Message to retrieve the status of an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_attestation_status(&self, attestation_id: u128) -> AttestationStatus {
+            let a = self.get_attestation(attestation_id);
+            if !a.is_revoked && self.superseded_by.contains_key(&attestation_id) {
+                return AttestationStatus::Superseded;
+            }
+            a.status(
+                &self.get_signers(attestation_id),
+                &self.get_rejecters(attestation_id),
+                self.poa.contains_key(&attestation_id),
+                env::block_timestamp(),
+            )
+        }
+
+        /// # Message to retrieve a Proof of Signature by attestation id.
+        ///
+        /// This function retrieves a stored Proof of Signature by  attestation id.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_proof_of_signature(&self, attestation_id: u128) -> Vec<ProofOfSignature> {
+            self.pos.get(&attestation_id).unwrap().clone()
+        }
+
+        /// # Message to retrieve the signatories who signed an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_signers(&self, attestation_id: u128) -> Vec<String> {
+            self.signed_attestation
+                .get(&attestation_id)
+                .map(|signers| {
+                    let mut signers: Vec<String> = signers
+                        .iter()
+                        .map(|signer| String::from(signer.as_str()))
+                        .collect();
+                    signers.sort();
+                    signers
+                })
+                .unwrap_or_default()
+        }
+
+        /// # Message to retrieve the rejections of an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_rejections(&self, attestation_id: u128) -> Vec<ProofOfRejection> {
+            self.rejections
+                .get(&attestation_id)
+                .cloned()
+                .unwrap_or_default()
+        }
+
+        /// # Message to retrieve the signatories who rejected an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_rejecters(&self, attestation_id: u128) -> Vec<String> {
+            self.get_rejections(attestation_id)
+                .into_iter()
+                .map(|r| r.creator)
+                .collect()
+        }
+
+        /// # Message to retrieve the signatories allowed to sign an Attestation next.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn next_expected_signers(&self, attestation_id: u128) -> Vec<String> {
+            let a = self.get_attestation(attestation_id);
+            a.next_expected_signers(
+                &self.get_signers(attestation_id),
+                &self.get_rejecters(attestation_id),
+            )
+        }
+
+        /// # Message to retrieve a Proof of Agreement by attestation id.
+        ///
+        /// This function retrieves a stored Proof of Agreement by  attestation id.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_proof_of_agreement(&self, attestation_id: u128) -> Option<ProofOfAgreement> {
+            self.poa.get(&attestation_id).cloned()
+        }
+
+        /// # Message to retrieve the roles bound to the signatories of an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_signatory_roles(&self, attestation_id: u128) -> Vec<SignatoryRole> {
+            self.get_attestation(attestation_id).signatory_roles
+        }
+
+        /// # Message to retrieve the signatory policies a signatory of an Attestation must satisfy.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        /// * `signatory` - Address of the signatory.
+        pub fn get_signatory_policies(
+            &self,
+            attestation_id: u128,
+            signatory: String,
+        ) -> Vec<SignatoryPolicy> {
+            let a = self.get_attestation(attestation_id);
+            let s = self.get_schema(a.schema_id);
+            a.policies_for(&s, &signatory)
+        }
+
+        /// # Message to retrieve the nft status of an Attestation.
+        ///
+        /// # Arguments
+        ///
+        /// * `attestation_id` - String representing the ID of the Attestation.
+        pub fn get_nft_status(&self, attestation_id: u128) -> Option<NftStatus> {
+            self.nft_status.get(&attestation_id).cloned()
+        }
+
+        /// # Message to retrieve an Attestations  for a specific user by schema id & his address.
+        ///
+        /// This function retrieves a stored Attestation by  by schema id & user address.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - String representing the ID of the Schema.
+        /// * `caller` - Address of a specific user.
+        pub fn get_user_attestations(&self, schema_id: u128, caller: String) -> Vec<Attestation> {
+            self.user_attestation
+                .get(&schema_id) // Get the map for schema_id
+                .and_then(|caller_map| caller_map.get(&caller)) // Get the attestations for caller
+                .cloned() // Clone the vector 
+                .unwrap_or_else(Vec::new) // If None, return an empty Vec
+        }
+    }
+    /// Token id of a Proof-of-Agreement nft, derived from the attestation id and the token owner.
+    pub fn poa_token_id(attestation_id: u128, owner: &AccountId) -> String {
+        format!("{}:{}", attestation_id, owner)
+    }
+
+    /// Tokens minted for a completed agreement according to the schema's mint policy.
+    pub fn poa_mints(a: &Attestation, policy: &PoaMintPolicy) -> Vec<NftMint> {
+        let owners: Vec<AccountId> = match policy {
+            PoaMintPolicy::Signatories => a
+                .signatories
+                .iter()
+                .map(|signatory| signatory.parse().expect("Invalid address"))
+                .collect(),
+            PoaMintPolicy::Recipient if !a.recipient.is_empty() => {
+                vec![a.recipient.parse().expect("Invalid address")]
+            }
+            // Agreements without a recipient fall back to the app
+            PoaMintPolicy::Recipient | PoaMintPolicy::App => vec![env::current_account_id()],
+        };
+
+        owners
+            .into_iter()
+            .map(|owner| NftMint {
+                token_id: poa_token_id(a.attestation_id, &owner),
+                token_owner_id: owner,
+            })
+            .collect()
+    }
+
+    /// Checks the number of items of a batch.
+    fn assert_batch(len: usize) {
+        assert!(len > 0, "empty batch!");
+        assert!(len <= MAX_BATCH, "batch too large!");
+    }
+
+    /// Checks the gas the nft mints of a batch reserve.
+    fn assert_batch_mint_gas(gas: Gas) {
+        assert!(
+            gas <= MAX_BATCH_MINT_GAS,
+            "batch mints exceed the gas limit!"
+        );
+    }
+
+    /// Bytes an attestation takes once stored, with the copies kept for its recipient and signatories
+    /// and its entries in the document index.
+    pub fn attestation_storage_bytes(a: &Attestation) -> u128 {
+        let copies = 1 + a.signatories.len() as u128 + !a.recipient.is_empty() as u128;
+        let bytes = borsh::to_vec(a)
+            .expect("Failed to serialize attestation")
+            .len() as u128;
+        let index_bytes: u128 = a
+            .documents
+            .iter()
+            .map(|d| 2 * d.hash.len() as u128 + 16 + STORAGE_ENTRY_OVERHEAD)
+            .sum();
+        (bytes + STORAGE_ENTRY_OVERHEAD) * copies + index_bytes
+    }
+
+    /// Bytes a bulk attestation takes once stored.
+    pub fn bulk_storage_bytes(bulk: &BulkAttestation) -> u128 {
+        let bytes = borsh::to_vec(bulk)
+            .expect("Failed to serialize bulk attestation")
+            .len() as u128;
+        bytes + STORAGE_ENTRY_OVERHEAD
+    }
+
+    /// Bytes a proof of signature takes once stored, with its signer entry.
+    pub fn pos_storage_bytes(pos: &ProofOfSignature) -> u128 {
+        // The public key is recorded by the contract
+        let bytes = borsh::to_vec(pos).expect("Failed to serialize proof").len() as u128 + 32;
+        bytes + pos.creator.len() as u128 + 2 * STORAGE_ENTRY_OVERHEAD
+    }
+
+    /// Lowercase hex of `bytes`, used as a map key.
+    pub fn hex_key(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn storage_cost(bytes: u128) -> NearToken {
+        NEAR_PER_STORAGE.saturating_mul(bytes)
+    }
+
+    /// Deposit and gas for an `nft_mint` call, sized by the length of its JSON arguments.
+    pub fn mint_cost(args_len: usize) -> (NearToken, Gas) {
+        let storage_bytes = MINT_STORAGE_OVERHEAD + args_len as u128;
+        let deposit = NEAR_PER_STORAGE.saturating_mul(storage_bytes);
+        let gas = Gas::from_tgas(MINT_BASE_TGAS + (args_len as u64) / 1024);
+        (deposit, gas)
+    }
+
+    /// Gas reserved by `mints` nft mints scheduled together with their callback, for mint args
+    /// under 1 KiB.
+    pub fn mint_gas(mints: usize) -> Gas {
+        if mints == 0 {
+            return Gas::from_gas(0);
+        }
+        Gas::from_tgas(MINT_BASE_TGAS * mints as u64).saturating_add(CALLBACK_GAS)
+    }
+
+    pub fn daosign_metadata() -> TokenMetadata {
+        TokenMetadata {
+            title: Some("Olympus Mons".into()),
+            description: Some("The tallest mountain in the charted solar system".into()),
+            media: None,
+            media_hash: None,
+            copies: Some(1u64),
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
     use std::{any::Any, str::FromStr};
 
+    use crate::legacy::{
+        LegacyAttestation, LegacyDAOSignApp, LegacyProofOfAgreement, LegacyProofOfSignature,
+        LegacySchema, LegacySchemaDefinition, LegacySchemaMetadata, LegacySignatoryPolicy,
+    };
     use borsh::BorshDeserialize;
-    use daosign_app::DAOSignApp;
-    use daosign_attestation::{Attestation, AttestationResult};
-    use daosign_proof_of_signature::ProofOfSignature;
-    use daosign_schema::{Schema, SchemaDefinition, SchemaMetadata, SignatoryPolicy};
+    use daosign_app::{
+        attestation_storage_bytes, mint_gas, poa_token_id, storage_cost, DAOSignApp, NftMint,
+        NftStatus, MAX_BATCH, MAX_BATCH_MINT_GAS,
+    };
+    use daosign_attestation::{
+        disclosure::{self, SaltedField},
+        field_commitment, merkle, Attestation, AttestationResult, AttestationStatus,
+        BulkAttestation, DocumentRef, HashAlgorithm, PolicyRef, SignatoryRole, SigningOrder,
+        SigningThreshold,
+    };
+    use daosign_proof_of_signature::{ProofOfRejection, ProofOfSignature};
+    use daosign_schema::{
+        AttestationRequirement, CompareOp, FieldPredicate, FieldPrivacy, PoaMintPolicy, PolicyExpr,
+        PolicyOperator, Schema, SchemaDefinition, SchemaMetadata, SignatoryPolicy,
+    };
     use ed25519_dalek::{Keypair, PublicKey as DalekPublicKey, Signature, Signer};
     use near_sdk::{
-        bs58,
-        test_utils::{accounts, VMContextBuilder},
-        testing_env, AccountId, NearToken, PublicKey, VMContext,
+        bs58, env,
+        test_utils::{accounts, get_created_receipts, VMContextBuilder},
+        testing_env, AccountId, NearToken, PromiseError, PromiseResult, PublicKey, VMContext,
     };
     use rand::rngs::OsRng;
 
@@ -465,9 +1933,30 @@ mod test {
         signer.sign(message)
     }
 
+    fn factory_id() -> AccountId {
+        "factory.testnet".parse().expect("Invalid address")
+    }
+
     // Create DAOSignApp instance for testing
     fn create_daosign_app() -> DAOSignApp {
-        DAOSignApp::new()
+        DAOSignApp::new(Some(factory_id()))
+    }
+
+    // Store a schema and resolve its collection deployment as successful
+    fn store_active_schema(app: &mut DAOSignApp, schema: Schema) {
+        app.store_schema(schema.clone());
+        let schema_id = app.schema_id - 1;
+
+        if schema.metadata.is_nft {
+            let collection = app.pending_collections.get(&schema_id).unwrap().clone();
+            app.on_collection_deployed(
+                schema_id,
+                collection,
+                accounts(0),
+                NearToken::from_near(10),
+                Ok(true),
+            );
+        }
     }
 
     /// Convert Dalek PublicKey to `near_sdk::PublicKey`
@@ -480,6 +1969,11 @@ mod test {
     }
 
     fn update_states(id: Option<usize>) -> Keypair {
+        update_states_at(id, 0)
+    }
+
+    // Set up the environment of a signer at the given block timestamp
+    fn update_states_at(id: Option<usize>, block_timestamp: u64) -> Keypair {
         // Unwrap `id` or use `0` as default
         let account_index = id.unwrap_or(0);
 
@@ -492,12 +1986,41 @@ mod test {
             .signer_account_id(accounts(account_index))  // ✅ Set signer dynamically
             .signer_account_pk(signer_pk_near)
             .attached_deposit(NearToken::from_near(10))  // Attach some NEAR for deployment
+            .block_timestamp(block_timestamp)
             .build();
 
         testing_env!(context); // ✅ Apply mock environment
         signer_pk
     }
 
+    // Attach another deposit to the calls of a signer set up by `update_states`
+    fn attach_deposit(id: usize, signer_pk: &Keypair, deposit: NearToken) {
+        let context: VMContext = VMContextBuilder::new()
+            .current_account_id(accounts(id))
+            .signer_account_id(accounts(id))
+            .signer_account_pk(convert_public_key_to_near(&signer_pk.public))
+            .attached_deposit(deposit)
+            .build();
+        testing_env!(context);
+    }
+
+    // Set the results of the promises resolved by the next callback
+    fn set_promise_results(account_index: usize, results: Vec<PromiseResult>) {
+        let context: VMContext = VMContextBuilder::new()
+            .current_account_id(accounts(account_index))
+            .signer_account_id(accounts(account_index))
+            .predecessor_account_id(accounts(account_index))
+            .build();
+
+        testing_env!(
+            context,
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            results,
+        );
+    }
+
     /// Utility method to check if the result contains the expected error message.
     fn check_error(msg: &str, result: Result<(), Box<dyn Any + Send>>) -> bool {
         if let Err(err) = result {
@@ -553,14 +2076,29 @@ mod test {
             is_revoked: false,
             revoked_at: 0,
             revoke_signature: vec![0; 65],
+            signatory_roles: vec![],
+            threshold: None,
+            signing_order: None,
+            sign_deadline: None,
+            is_cancelled: false,
+            cancelled_at: 0,
+            cancel_signature: vec![],
+            documents: vec![],
+            fields_root: vec![],
+            refers_to: None,
+            supersedes: None,
+            stored_at: 0,
         };
         attestation
     }
-    fn create_pos(a_id: u128, creator: String) -> ProofOfSignature {
+    fn create_pos(a: &Attestation, creator: String) -> ProofOfSignature {
         let pos = ProofOfSignature {
-            attestation_id: a_id,
+            attestation_id: a.attestation_id,
             creator,
             created_at: 0,
+            content_hash: a.content_hash(),
+            signed_at: 0,
+            public_key: vec![],
             signature: vec![0],
         };
         pos
@@ -574,27 +2112,31 @@ mod test {
                 attestation_type: "agreement".to_string(),
                 nft_name: "nft_name".to_string(),
                 nft_symbol: "nft_symbol".to_string(),
-                collection_id: "test.collection.testnet".parse().expect("Invalid address"),
+                collection_id: String::new(),
                 creator: creator.parse().expect("Invalid address"),
                 created_at: 1,
                 is_nft: true,
                 is_public: false,
                 is_revokable: true,
                 expire_in: 0,
+                poa_mint_policy: PoaMintPolicy::App,
             },
             signatory_policy: vec![SignatoryPolicy {
-                operator: 0x01,
+                operator: PolicyOperator::And,
                 signatory_description: "role".to_string(),
                 required_schema_id: vec![0],
+                expression: None,
             }],
             schema_definition: vec![
                 SchemaDefinition {
                     definition_type: "string".to_string(),
                     definition_name: "vacancies".to_string(),
+                    privacy: FieldPrivacy::Plaintext,
                 },
                 SchemaDefinition {
                     definition_type: "uint256".to_string(),
                     definition_name: "salary".to_string(),
+                    privacy: FieldPrivacy::Plaintext,
                 },
             ],
             signature: vec![0; 65],
@@ -602,6 +2144,130 @@ mod test {
         schema_data
     }
 
+    #[test]
+    fn test_migrate() {
+        update_states(Some(0));
+        let signer = String::from(accounts(0).as_str());
+        let signatory = String::from(accounts(1).as_str());
+
+        let schema = LegacySchema {
+            schema_id: 0,
+            metadata: LegacySchemaMetadata {
+                name: String::from("DaoSign Vacancy"),
+                description: String::new(),
+                attestation_type: String::from("agreement"),
+                nft_name: String::from("nft_name"),
+                nft_symbol: String::from("nft_symbol"),
+                collection_id: String::from("test.collection.testnet"),
+                creator: signer.clone(),
+                created_at: 1,
+                is_nft: true,
+                is_public: false,
+                is_revokable: true,
+                expire_in: 0,
+            },
+            signatory_policy: vec![LegacySignatoryPolicy {
+                operator: 0x02,
+                signatory_description: String::from("Employee"),
+                required_schema_id: vec![0],
+            }],
+            schema_definition: vec![LegacySchemaDefinition {
+                definition_type: String::from("uint256"),
+                definition_name: String::from("salary"),
+            }],
+            signature: vec![1; 64],
+        };
+        let attestation = LegacyAttestation {
+            attestation_id: 0,
+            schema_id: 0,
+            attestation_result: vec![AttestationResult {
+                attestation_result_type: String::from("uint256"),
+                name: String::from("salary"),
+                value: vec![16, 0],
+            }],
+            creator: signer.clone(),
+            recipient: signer.clone(),
+            created_at: 5,
+            signatories: vec![signer.clone(), signatory.clone()],
+            signature: vec![2; 64],
+            is_revoked: false,
+            revoked_at: 0,
+            revoke_signature: vec![],
+        };
+        let proofs: Vec<LegacyProofOfSignature> = [&signer, &signatory]
+            .iter()
+            .enumerate()
+            .map(|(i, creator)| LegacyProofOfSignature {
+                attestation_id: 0,
+                creator: creator.to_string(),
+                created_at: 10 + i as u64,
+                signature: vec![3 + i as u8; 64],
+            })
+            .collect();
+
+        let legacy = LegacyDAOSignApp {
+            schema_id: 1,
+            attestation_id: 1,
+            collections: HashMap::from([(0, "test.collection.testnet".parse().unwrap())]),
+            schemas: HashMap::from([(0, schema)]),
+            attestations: HashMap::from([(0, attestation.clone())]),
+            poa: HashMap::from([(
+                0,
+                vec![LegacyProofOfAgreement {
+                    attestation_id: 0,
+                    signatures: vec![vec![3; 64], vec![4; 64]],
+                }],
+            )]),
+            pos: HashMap::from([(0, proofs)]),
+            signed_attestation: HashMap::from([(
+                0,
+                HashMap::from([(signer.clone(), true), (signatory.clone(), true)]),
+            )]),
+            user_attestation: HashMap::from([(
+                0,
+                HashMap::from([(signer.clone(), vec![attestation])]),
+            )]),
+        };
+        env::state_write(&legacy);
+
+        let app = DAOSignApp::migrate(Some(factory_id()));
+
+        assert_eq!(app.schema_id, 1);
+        assert_eq!(app.attestation_id, 1);
+        assert_eq!(app.factory_id, Some(factory_id()));
+        // Legacy collections stay active
+        assert!(app.is_schema_active(0));
+
+        let schema = app.get_schema(0);
+        assert_eq!(schema.signatory_policy[0].operator, PolicyOperator::Or);
+        assert_eq!(schema.signatory_policy[0].expression, None);
+        assert_eq!(schema.schema_definition[0].privacy, FieldPrivacy::Plaintext);
+        assert_eq!(schema.metadata.poa_mint_policy, PoaMintPolicy::App);
+
+        // Stored at the migration, not at the signed creation time
+        let a = app.get_attestation(0);
+        assert_eq!(a.stored_at, env::block_timestamp());
+        assert_eq!(a.signatories, vec![signer.clone(), signatory.clone()]);
+        assert_eq!(app.get_user_attestations(0, signer.clone()), vec![a]);
+
+        assert_eq!(app.get_signers(0).len(), 2);
+        assert_eq!(app.get_proof_of_signature(0)[1].signed_at, 11);
+        let poa = app.get_proof_of_agreement(0).unwrap();
+        assert_eq!(poa.signatures[1].account_id, signatory);
+        assert_eq!(poa.signatures[1].signature, vec![4; 64]);
+        assert_eq!(poa.signatures[1].signed_at, 11);
+
+        // The agreement was minted to the app under the attestation id
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Minted));
+        assert_eq!(
+            app.nft_tokens.get(&0),
+            Some(&vec![NftMint {
+                token_id: String::from("0"),
+                token_owner_id: env::current_account_id(),
+            }])
+        );
+    }
+
     #[test]
     fn test_store_schema() {
         let caller = update_states(Some(0));
@@ -617,68 +2283,2028 @@ mod test {
         schema.signature = signature.to_bytes().to_vec();
         // Store schema
         app.store_schema(schema.clone());
-        let coll = app.collections.get(&0);
 
-        println!("collection: {:?}", coll);
+        // Collection is deployed under the factory, named by the app
+        let collection: AccountId = "schema-0.factory.testnet".parse().unwrap();
+        assert_eq!(app.get_collection_account(0), collection);
 
-        // Verify schema is stored
+        // Verify schema is stored unchanged and waits for its collection
         assert_eq!(app.schemas.len(), 1);
         assert_eq!(app.schemas.get(&0), Some(&schema));
+        assert_eq!(app.pending_collections.get(&0), Some(&collection));
+        assert!(!app.is_schema_active(0));
+
+        app.on_collection_deployed(
+            0,
+            collection.clone(),
+            signer,
+            NearToken::from_near(10),
+            Ok(true),
+        );
+
+        assert_eq!(app.collections.get(&0), Some(&collection));
+        assert!(app.pending_collections.is_empty());
+        assert!(app.is_schema_active(0));
     }
 
     #[test]
-    fn test_schema_unauthorized_schema_creator() {
+    fn test_schema_collection_deployment_failed() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
             let mut app = create_daosign_app();
 
+            let signatory: AccountId = accounts(1);
             let signer: AccountId = accounts(0);
 
             let mut schema = create_schema(String::from(signer.as_str()));
-            schema.metadata.creator = "test.creator".parse().expect("invalid creator mock id"); // Change to an unauthorized creator
             let message = schema.to_ed25519_message();
             let signature = sign_transaction(&message, &caller);
             schema.signature = signature.to_bytes().to_vec();
-
-            // Attempt to store the schema (this should trigger an error)
             app.store_schema(schema.clone());
+
+            let collection = app.pending_collections.get(&0).unwrap().clone();
+            assert!(!app.on_collection_deployed(
+                0,
+                collection,
+                signer.clone(),
+                NearToken::from_near(10),
+                Err(PromiseError::Failed),
+            ));
+            assert!(app.collections.is_empty());
+            assert!(!app.is_schema_active(0));
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
         });
 
         assert!(
-            check_error("unauthorized schema creator!", result),
+            check_error("schema is not active!", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_schema_empty_schema_definition() {
+    fn test_retry_collection_deployment() {
         let caller = update_states(Some(0));
 
-        let result = std::panic::catch_unwind(|| {
-            let mut app = create_daosign_app();
-            let signer: AccountId = accounts(0);
+        let mut app = create_daosign_app();
+        let signer: AccountId = accounts(0);
 
-            let mut schema = create_schema(String::from(signer.as_str()));
-            schema.schema_definition.clear();
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        app.store_schema(schema.clone());
 
-            let message = schema.to_ed25519_message();
-            let signature = sign_transaction(&message, &caller);
-            schema.signature = signature.to_bytes().to_vec();
+        let collection = app.pending_collections.get(&0).unwrap().clone();
+        app.on_collection_deployed(
+            0,
+            collection.clone(),
+            signer.clone(),
+            NearToken::from_near(10),
+            Err(PromiseError::Failed),
+        );
+        assert!(!app.is_schema_active(0));
 
-            // Attempt to store the schema (this should trigger an error)
-            app.store_schema(schema.clone());
+        // The creator deploys the collection again
+        app.retry_collection_deployment(0);
+        assert_eq!(app.pending_collections.get(&0), Some(&collection));
+
+        app.on_collection_deployed(0, collection, signer, NearToken::from_near(10), Ok(true));
+        assert!(app.is_schema_active(0));
+        assert_eq!(app.get_schema(0), schema);
+
+        let result = std::panic::catch_unwind(move || {
+            app.retry_collection_deployment(0);
+        });
+        assert!(
+            check_error("collection already deployed!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_nft_schemas_signed_in_advance() {
+        let caller = update_states(Some(0));
+        let mut app = create_daosign_app();
+
+        // Schemas signed before either is stored don't depend on the id they get
+        let schemas: Vec<Schema> = ["First schema", "Second schema"]
+            .iter()
+            .map(|description| {
+                let mut schema = create_schema(String::from(accounts(0).as_str()));
+                schema.metadata.description = description.to_string();
+                let message = schema.to_ed25519_message();
+                schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+                schema
+            })
+            .collect();
+
+        app.store_schema(schemas[1].clone());
+        app.store_schema(schemas[0].clone());
+
+        assert_eq!(
+            app.pending_collections.get(&0),
+            Some(&"schema-0.factory.testnet".parse::<AccountId>().unwrap())
+        );
+        assert_eq!(
+            app.pending_collections.get(&1),
+            Some(&"schema-1.factory.testnet".parse::<AccountId>().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_schema_invalid_collection_account() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let mut schema = create_schema(String::from(accounts(0).as_str()));
+            schema.metadata.collection_id = String::from("test.collection.testnet");
+            let message = schema.to_ed25519_message();
+            schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+            app.store_schema(schema);
+        });
+
+        assert!(
+            check_error("invalid collection account!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_schema_unauthorized_schema_creator() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.metadata.creator = "test.creator".parse().expect("invalid creator mock id"); // Change to an unauthorized creator
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Attempt to store the schema (this should trigger an error)
+            app.store_schema(schema.clone());
+        });
+
+        assert!(
+            check_error("unauthorized schema creator!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_schema_empty_schema_definition() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.schema_definition.clear();
+
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Attempt to store the schema (this should trigger an error)
+            app.store_schema(schema.clone());
+        });
+
+        assert!(
+            check_error("empty schema definition!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_attestation() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        // Store schema
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        app.store_attestation(attestation.clone());
+
+        // Verify schema is stored
+        assert_eq!(app.attestations.len(), 1);
+        assert_eq!(app.attestations.get(&0), Some(&attestation));
+    }
+
+    #[test]
+    fn test_attestation_schema_does_not_exist() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+        });
+
+        assert!(
+            check_error("called `Option::unwrap()` on a `None` value", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_attestation_unauthorized_attestator() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+
+            attestation.creator = "test.test".parse().expect(" invalid precompile data");
+
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+        });
+
+        assert!(
+            check_error("unauthorized attestator!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_attestation_schema_already_expired() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.metadata.expire_in = 1;
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+        });
+
+        assert!(
+            check_error("schema already expired!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_attestation_length_mismatch() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            attestation.attestation_result.clear();
+
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+        });
+
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("attestation length mismatch!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_attestation_name_mismatch() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signer: AccountId = accounts(0);
+            let signatory: AccountId = accounts(1);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            attestation.attestation_result[0].name = String::from("foo");
+
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+        });
+
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("attestation name mismatch!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_attestation_type_mismatch() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signer: AccountId = accounts(0);
+            let signatory: AccountId = accounts(1);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            attestation.attestation_result[0].attestation_result_type = String::from("foo");
+
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+        });
+
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("attestation type mismatch!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_revoke() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+
+        // Store schema
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        app.store_attestation(attestation.clone());
+
+        let r_mes = attestation.to_ed25519_message_revoke();
+        let r_sig = sign_transaction(&r_mes, &caller);
+
+        attestation.revoke_signature = r_sig.to_bytes().to_vec();
+
+        app.store_revoke(attestation.attestation_id, attestation.revoke_signature);
+        // Verify schema is stored
+        assert_eq!(app.attestations.len(), 1);
+        if let Some(a) = app.attestations.get(&0) {
+            // Assert that `is_revoked` is `true`
+            assert_eq!(a.is_revoked, true, "Attestation should be revoked");
+        } else {
+            panic!("Expected Some(attestation), but got None");
+        }
+    }
+
+    #[test]
+    fn test_revoke_attestation_does_not_exist() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let caller = create_signer();
+            let signer: AccountId = accounts(0);
+            let signatory: AccountId = accounts(1);
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let r_mes = attestation.to_ed25519_message_revoke();
+            let r_sig = sign_transaction(&r_mes, &caller);
+
+            attestation.revoke_signature = r_sig.to_bytes().to_vec();
+
+            app.store_revoke(attestation.attestation_id, attestation.revoke_signature);
+        });
+
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("called `Option::unwrap()` on a `None` value", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_revoke_unauthorized_attestator() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signatory.as_str()),
+                String::from(signer.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+
+            let r_mes = attestation.to_ed25519_message_revoke();
+
+            let sec_caller = update_states(Some(1));
+            let r_sig = sign_transaction(&r_mes, &sec_caller);
+
+            attestation.revoke_signature = r_sig.to_bytes().to_vec();
+
+            app.store_revoke(attestation.attestation_id, attestation.revoke_signature);
+        });
+
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("unauthorized attestator!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_pos_and() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        // Store schema
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+
+        let pos_mes = pos.to_ed25519_message();
+        let pos_sig = sign_transaction(&pos_mes, &caller);
+
+        pos.signature = pos_sig.to_bytes().to_vec();
+
+        app.store_pos(pos);
+    }
+
+    #[test]
+    fn test_store_pos_or() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy[0].operator = PolicyOperator::Or;
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        // Store schema
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+
+        let pos_mes = pos.to_ed25519_message();
+        let pos_sig = sign_transaction(&pos_mes, &caller);
+
+        pos.signature = pos_sig.to_bytes().to_vec();
+
+        app.store_pos(pos);
+    }
+
+    #[test]
+    fn test_store_pos_not() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy[0].required_schema_id.clear();
+        schema.signatory_policy[0].required_schema_id = [1].to_vec();
+        schema.signatory_policy[0].operator = PolicyOperator::Not;
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        // Store schema
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+
+        let pos_mes = pos.to_ed25519_message();
+        let pos_sig = sign_transaction(&pos_mes, &caller);
+
+        pos.signature = pos_sig.to_bytes().to_vec();
+
+        app.store_pos(pos);
+    }
+
+    #[test]
+    fn test_pos_invalid_signatory_address() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+
+            let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+
+            let sec_caller = update_states(Some(1));
+            let pos_mes = pos.to_ed25519_message();
+            let pos_sig = sign_transaction(&pos_mes, &sec_caller);
+
+            pos.signature = pos_sig.to_bytes().to_vec();
+            update_states(Some(2));
+
+            app.store_pos(pos);
+        });
+
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("Invalid signatory address.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_schema_unsupported_operator() {
+        let signer: AccountId = accounts(0);
+
+        let mut schema =
+            serde_json::to_value(create_schema(String::from(signer.as_str()))).unwrap();
+        schema["signatory_policy"][0]["operator"] = serde_json::json!(0x04);
+
+        // Unknown operators are rejected when the schema arguments are decoded
+        let err = serde_json::from_value::<Schema>(schema).unwrap_err();
+        assert!(err.to_string().contains("Unsupported operator"));
+    }
+
+    #[test]
+    fn test_schema_invalid_policy_expression() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.signatory_policy[0].required_schema_id.clear();
+            schema.signatory_policy[0].expression = Some(PolicyExpr::Threshold {
+                k: 2,
+                of: vec![PolicyExpr::Schema(0)],
+            });
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+
+            app.store_schema(schema.clone());
+        });
+
+        assert!(
+            check_error("invalid policy threshold!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_pos_policy_expression() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        // Holds schema 0 and not schema 1, or 1 of [1, 2]
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy[0].required_schema_id.clear();
+        schema.signatory_policy[0].expression = Some(PolicyExpr::Or(vec![
+            PolicyExpr::And(vec![
+                PolicyExpr::Schema(0),
+                PolicyExpr::Not(Box::new(PolicyExpr::Schema(1))),
+            ]),
+            PolicyExpr::Threshold {
+                k: 1,
+                of: vec![PolicyExpr::Schema(1), PolicyExpr::Schema(2)],
+            },
+        ]));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+
+        let pos_mes = pos.to_ed25519_message();
+        let pos_sig = sign_transaction(&pos_mes, &caller);
+
+        pos.signature = pos_sig.to_bytes().to_vec();
+
+        app.store_pos(pos);
+        assert_eq!(app.get_proof_of_signature(0).len(), 1);
+    }
+
+    #[test]
+    fn test_pos_insufficient_attestations() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.signatory_policy[0].required_schema_id.push(1);
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            // Store schema
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+
+            app.store_attestation(attestation.clone());
+
+            let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+
+            let pos_mes = pos.to_ed25519_message();
+            let pos_sig = sign_transaction(&pos_mes, &caller);
+
+            pos.signature = pos_sig.to_bytes().to_vec();
+
+            app.store_pos(pos);
+        });
+        // Check that the error occurred and contains the expected message
+        assert!(
+            check_error("insufficient attestations.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_poa_two_signatory() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+
+        // Store schema
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        // Store attestation
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        let pos_sig = sign_transaction(&pos_mes, &caller);
+
+        pos.signature = pos_sig.to_bytes().to_vec();
+
+        app.store_pos(pos);
+
+        let sec_caller = update_states(Some(1));
+
+        let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let sec_pos_mes = sec_pos.to_ed25519_message();
+        let sec_pos_sig = sign_transaction(&sec_pos_mes, &sec_caller);
+
+        sec_pos.signature = sec_pos_sig.to_bytes().to_vec();
+
+        app.store_pos(sec_pos);
+        assert!(app.poa.len() == 1);
+
+        // The agreement verifies on its own
+        let poa = app.get_proof_of_agreement(0).unwrap();
+        assert_eq!(poa.content_hash, attestation.content_hash());
+        assert_eq!(
+            poa.signatures
+                .iter()
+                .map(|s| s.account_id.as_str())
+                .collect::<Vec<&str>>(),
+            vec![signer.as_str(), signatory.as_str()]
+        );
+        assert!(poa.verify());
+    }
+
+    #[test]
+    fn test_nft_mint_failed_and_retry() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy.clear();
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+
+        // Mints the nft to the recipient
+        app.store_attestation(attestation.clone());
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Pending));
+
+        let mints = app.pending_mints.get(&0).unwrap().clone();
+        assert_eq!(mints.len(), 1);
+        assert_eq!(mints[0].token_owner_id, signer);
+
+        set_promise_results(0, vec![PromiseResult::Failed]);
+        assert!(!app.on_nft_minted(0, mints.clone()));
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Failed));
+        assert_eq!(app.pending_mints.get(&0), Some(&mints));
+
+        // The retry pays for the mint again
+        attach_deposit(0, &caller, NearToken::from_near(1));
+        app.retry_nft_mint(0);
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Pending));
+
+        set_promise_results(0, vec![PromiseResult::Successful(vec![])]);
+        assert!(app.on_nft_minted(0, mints));
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Minted));
+        assert!(app.pending_mints.get(&0).is_none());
+
+        let tokens = app.nft_tokens.get(&0).unwrap().clone();
+        assert_eq!(tokens, mints);
+
+        set_promise_results(0, vec![PromiseResult::Successful(vec![])]);
+        assert!(app.on_nft_burned(0, tokens));
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Burned));
+        assert!(app.nft_tokens.get(&0).is_none());
+    }
+
+    #[test]
+    fn test_nft_minted_after_revoke() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy.clear();
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(accounts(1).as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+        let mints = app.pending_mints.get(&0).unwrap().clone();
+
+        // Revoked while the mint is pending, there is no token to burn yet
+        let r_mes = attestation.to_ed25519_message_revoke();
+        let r_sig = sign_transaction(&r_mes, &caller);
+        app.store_revoke(0, r_sig.to_bytes().to_vec());
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Pending));
+
+        // The minted token is burned right away
+        set_promise_results(0, vec![PromiseResult::Successful(vec![])]);
+        assert!(app.on_nft_minted(0, mints.clone()));
+        let collection = app.collections.get(&0).unwrap().clone();
+        assert!(get_created_receipts()
+            .iter()
+            .any(|receipt| receipt.receiver_id == collection));
+
+        set_promise_results(0, vec![PromiseResult::Successful(vec![])]);
+        assert!(app.on_nft_burned(0, mints));
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Burned));
+    }
+
+    #[test]
+    fn test_retry_nft_mint_insufficient_deposit() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.signatory_policy.clear();
+            let message = schema.to_ed25519_message();
+            schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(accounts(1).as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            let mints = app.pending_mints.get(&0).unwrap().clone();
+            set_promise_results(0, vec![PromiseResult::Failed]);
+            app.on_nft_minted(0, mints);
+
+            attach_deposit(0, &caller, NearToken::from_yoctonear(0));
+            app.retry_nft_mint(0);
+        });
+
+        assert!(
+            check_error("insufficient storage deposit!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_retry_nft_mint_not_failed() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.signatory_policy.clear();
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            app.retry_nft_mint(0);
+        });
+
+        assert!(
+            check_error("nft mint is not failed!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_poa_mint_to_signatories() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.metadata.poa_mint_policy = PoaMintPolicy::Signatories;
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+
+        let sec_caller = update_states(Some(1));
+        let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let sec_pos_mes = sec_pos.to_ed25519_message();
+        sec_pos.signature = sign_transaction(&sec_pos_mes, &sec_caller)
+            .to_bytes()
+            .to_vec();
+        app.store_pos(sec_pos);
+
+        // One token per signatory
+        let mints = app.pending_mints.get(&0).unwrap();
+        assert_eq!(mints.len(), 2);
+        assert_eq!(mints[0].token_id, poa_token_id(0, &signer));
+        assert_eq!(mints[0].token_owner_id, signer);
+        assert_eq!(mints[1].token_id, poa_token_id(0, &signatory));
+        assert_eq!(mints[1].token_owner_id, signatory);
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Pending));
+    }
+
+    #[test]
+    fn test_store_poa_without_nft() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.metadata.is_nft = false;
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+
+        // No collection is deployed for non-nft schemas
+        app.store_schema(schema.clone());
+        assert!(app.pending_collections.is_empty());
+        assert!(app.is_schema_active(0));
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+
+        let sec_caller = update_states(Some(1));
+        let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let sec_pos_mes = sec_pos.to_ed25519_message();
+        sec_pos.signature = sign_transaction(&sec_pos_mes, &sec_caller)
+            .to_bytes()
+            .to_vec();
+        app.store_pos(sec_pos);
+
+        // PoA is recorded without minting
+        assert!(app.get_proof_of_agreement(0).is_some());
+        assert!(app.pending_mints.is_empty());
+        assert_eq!(app.get_nft_status(0), None);
+    }
+
+    #[test]
+    fn test_store_poa_with_nft() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+
+        let sec_caller = update_states(Some(1));
+        let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let sec_pos_mes = sec_pos.to_ed25519_message();
+        sec_pos.signature = sign_transaction(&sec_pos_mes, &sec_caller)
+            .to_bytes()
+            .to_vec();
+        app.store_pos(sec_pos);
+
+        // PoA is recorded and its nft is minted to the app (current account of the last call)
+        assert!(app.get_proof_of_agreement(0).is_some());
+        let mints = app.pending_mints.get(&0).unwrap();
+        assert_eq!(mints.len(), 1);
+        assert_eq!(mints[0].token_owner_id, accounts(1));
+        assert_eq!(app.get_nft_status(0), Some(NftStatus::Pending));
+    }
+
+    #[test]
+    fn test_store_pos_signatory_role() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        // Nobody holds an attestation of schema 7 required from employees
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy[0].signatory_description = "Employer".to_string();
+        schema.signatory_policy.push(SignatoryPolicy {
+            operator: PolicyOperator::And,
+            signatory_description: "Employee".to_string(),
+            required_schema_id: vec![7],
+            expression: None,
+        });
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation.signatory_roles = vec![
+            SignatoryRole {
+                signatory: String::from(signer.as_str()),
+                policy: PolicyRef::Role("Employer".to_string()),
+            },
+            SignatoryRole {
+                signatory: String::from(signatory.as_str()),
+                policy: PolicyRef::Index(1),
+            },
+        ];
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        assert_eq!(app.get_signatory_roles(0), attestation.signatory_roles);
+        assert_eq!(
+            app.get_signatory_policies(0, String::from(signer.as_str())),
+            vec![schema.signatory_policy[0].clone()]
+        );
+
+        // The employer is only checked against the employer policy
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+        assert_eq!(app.get_proof_of_signature(0).len(), 1);
+
+        // The employee misses the attestation required by its role
+        let result = std::panic::catch_unwind(move || {
+            let sec_caller = update_states(Some(1));
+            let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+            let sec_pos_mes = sec_pos.to_ed25519_message();
+            sec_pos.signature = sign_transaction(&sec_pos_mes, &sec_caller)
+                .to_bytes()
+                .to_vec();
+            app.store_pos(sec_pos);
+        });
+        assert!(
+            check_error("insufficient attestations.", result),
+            "Unexpected error message"
+        );
+    }
+
+    // Store a schema requiring a non-revoked attestation of schema 0 from alice
+    // with a salary of at least `min_salary`, and an attestation signed by alice and bob
+    fn store_salary_agreement(app: &mut DAOSignApp, caller: &Keypair, min_salary: Vec<u8>) {
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy[0].required_schema_id.clear();
+        schema.signatory_policy[0].expression =
+            Some(PolicyExpr::Attestation(AttestationRequirement {
+                schema_id: 0,
+                not_revoked: true,
+                not_expired: true,
+                trusted_creators: vec![String::from(signer.as_str())],
+                fields: vec![FieldPredicate {
+                    name: "salary".to_string(),
+                    op: CompareOp::Gte,
+                    values: vec![min_salary],
+                }],
+                max_age: None,
+            }));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+    }
+
+    #[test]
+    fn test_store_pos_attestation_requirement() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+        store_salary_agreement(&mut app, &caller, vec![16, 0]);
+
+        let mut pos = create_pos(&app.get_attestation(0), String::from(accounts(0).as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+        assert_eq!(app.get_proof_of_signature(0).len(), 1);
+    }
+
+    #[test]
+    fn test_pos_attestation_requirement_not_met() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            store_salary_agreement(&mut app, &caller, vec![32, 0]);
+
+            let mut pos = create_pos(&app.get_attestation(0), String::from(accounts(0).as_str()));
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+            app.store_pos(pos);
+        });
+
+        assert!(
+            check_error("insufficient attestations.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_pos_attestation_requirement_revoked() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            store_salary_agreement(&mut app, &caller, vec![16, 0]);
+
+            // Second agreement with a salary below the requirement
+            let mut attestation = create_attestation(
+                String::from(accounts(0).as_str()),
+                String::from(accounts(1).as_str()),
+            );
+            attestation.attestation_result[1].value = vec![1, 0];
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            // Revoked attestations no longer satisfy the policy
+            let r_mes = app.get_attestation(0).to_ed25519_message_revoke();
+            let r_sig = sign_transaction(&r_mes, &caller);
+            app.store_revoke(0, r_sig.to_bytes().to_vec());
+
+            let mut pos = create_pos(&app.get_attestation(1), String::from(accounts(0).as_str()));
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+            app.store_pos(pos);
+        });
+
+        assert!(
+            check_error("insufficient attestations.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_pos_attestation_requirement_superseded() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            store_salary_agreement(&mut app, &caller, vec![16, 0]);
+
+            // Second agreement with a salary below the requirement
+            let mut attestation = create_attestation(
+                String::from(accounts(0).as_str()),
+                String::from(accounts(1).as_str()),
+            );
+            attestation.attestation_result[1].value = vec![1, 0];
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            // Superseded attestations no longer satisfy the policy
+            app.superseded_by.insert(0, 1);
+
+            let mut pos = create_pos(&app.get_attestation(1), String::from(accounts(0).as_str()));
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+            app.store_pos(pos);
+        });
+
+        assert!(
+            check_error("insufficient attestations.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_poa_threshold() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        // 2 of 3 signatories
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation
+            .signatories
+            .push(String::from(accounts(2).as_str()));
+        attestation.threshold = Some(SigningThreshold::Count(2));
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+        assert!(app.poa.is_empty());
+
+        let sec_caller = update_states(Some(1));
+        let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let sec_pos_mes = sec_pos.to_ed25519_message();
+        sec_pos.signature = sign_transaction(&sec_pos_mes, &sec_caller)
+            .to_bytes()
+            .to_vec();
+        app.store_pos(sec_pos);
+        assert!(app.get_proof_of_agreement(0).is_some());
+
+        // The last signature is still recorded
+        let third_caller = update_states(Some(2));
+        let mut third_pos = create_pos(&attestation, String::from(accounts(2).as_str()));
+        let third_pos_mes = third_pos.to_ed25519_message();
+        third_pos.signature = sign_transaction(&third_pos_mes, &third_caller)
+            .to_bytes()
+            .to_vec();
+        app.store_pos(third_pos);
+        assert_eq!(app.get_proof_of_signature(0).len(), 3);
+        assert_eq!(app.get_proof_of_agreement(0).unwrap().signatures.len(), 2);
+    }
+
+    #[test]
+    fn test_store_pos_signing_order() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        // The signatory signs after the creator
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation.signing_order = Some(SigningOrder::Sequential);
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        assert_eq!(
+            app.next_expected_signers(0),
+            vec![String::from(signer.as_str())]
+        );
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+
+        assert_eq!(
+            app.next_expected_signers(0),
+            vec![String::from(signatory.as_str())]
+        );
+    }
+
+    #[test]
+    fn test_store_pos_signing_order_after_rejection() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        // 1 of 2 signatories, the signatory signs after the creator
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation.signing_order = Some(SigningOrder::Sequential);
+        attestation.threshold = Some(SigningThreshold::Count(1));
+        let a_mes = attestation.to_ed25519_message();
+        attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        // The creator rejects instead of signing
+        let mut rejection = ProofOfRejection {
+            attestation_id: 0,
+            creator: String::from(signer.as_str()),
+            content_hash: app.get_attestation(0).content_hash(),
+            reason_hash: vec![1; 32],
+            created_at: 0,
+            signature: vec![],
+        };
+        let r_sig = sign_transaction(&rejection.to_ed25519_message(), &caller);
+        rejection.signature = r_sig.to_bytes().to_vec();
+        app.store_rejection(
+            0,
+            rejection.content_hash,
+            rejection.reason_hash,
+            rejection.signature,
+        );
+        assert_eq!(
+            app.next_expected_signers(0),
+            vec![String::from(signatory.as_str())]
+        );
+
+        // The next signatory still forms the agreement
+        let sec_caller = update_states(Some(1));
+        let mut pos = create_pos(&app.get_attestation(0), String::from(signatory.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &sec_caller).to_bytes().to_vec();
+        app.store_pos(pos);
+
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Completed);
+    }
+
+    #[test]
+    fn test_store_pos_out_of_order() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation.signing_order = Some(SigningOrder::Sequential);
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        // The signatory signs before the creator
+        let sec_caller = update_states(Some(1));
+        let mut pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &sec_caller).to_bytes().to_vec();
+        let result = std::panic::catch_unwind(move || {
+            app.store_pos(pos);
+        });
+
+        assert!(
+            check_error("out of order signature.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_cancel_attestation() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Draft);
+
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Pending);
+
+        let c_mes = attestation.to_ed25519_message_cancel();
+        let c_sig = sign_transaction(&c_mes, &caller);
+        app.cancel_attestation(0, c_sig.to_bytes().to_vec());
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Cancelled);
+        assert!(app.get_user_attestations(0, String::from(signatory.as_str()))[0].is_cancelled);
+
+        // Remaining signatories can't sign anymore
+        let sec_caller = update_states(Some(1));
+        let mut sec_pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let sec_pos_mes = sec_pos.to_ed25519_message();
+        sec_pos.signature = sign_transaction(&sec_pos_mes, &sec_caller)
+            .to_bytes()
+            .to_vec();
+        let result = std::panic::catch_unwind(move || {
+            app.store_pos(sec_pos);
+        });
+
+        assert!(
+            check_error("attestation cancelled.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_cancel_attestation_without_signatories() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            let signer: AccountId = accounts(0);
+
+            // A plain credential of a non revokable schema
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.signatory_policy.clear();
+            schema.metadata.is_revokable = false;
+            let message = schema.to_ed25519_message();
+            schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(accounts(1).as_str()),
+            );
+            attestation.signatories = vec![];
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            let c_mes = attestation.to_ed25519_message_cancel();
+            let c_sig = sign_transaction(&c_mes, &caller);
+            app.cancel_attestation(0, c_sig.to_bytes().to_vec());
+        });
+
+        assert!(
+            check_error("attestation awaits no signatures!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_cancel_burns_minted_nft() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+        let signer: AccountId = accounts(0);
+
+        // The nft is minted to the recipient as soon as the attestation is stored
+        let mut schema = create_schema(String::from(signer.as_str()));
+        schema.signatory_policy.clear();
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(accounts(1).as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let mints = app.pending_mints.get(&0).unwrap().clone();
+        set_promise_results(0, vec![PromiseResult::Successful(vec![])]);
+        app.on_nft_minted(0, mints.clone());
+
+        attach_deposit(0, &caller, NearToken::from_near(10));
+        let c_mes = attestation.to_ed25519_message_cancel();
+        let c_sig = sign_transaction(&c_mes, &caller);
+        app.cancel_attestation(0, c_sig.to_bytes().to_vec());
+
+        let collection = app.collections.get(&0).unwrap().clone();
+        assert!(get_created_receipts()
+            .iter()
+            .any(|receipt| receipt.receiver_id == collection));
+    }
+
+    #[test]
+    fn test_cancel_completed_attestation() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            store_active_schema(&mut app, schema.clone());
+
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            attestation.signatories = vec![String::from(signer.as_str())];
+            let a_mes = attestation.to_ed25519_message();
+            let a_sig = sign_transaction(&a_mes, &caller);
+            attestation.signature = a_sig.to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+            app.store_pos(pos);
+            assert_eq!(app.get_attestation_status(0), AttestationStatus::Completed);
+
+            let c_mes = attestation.to_ed25519_message_cancel();
+            let c_sig = sign_transaction(&c_mes, &caller);
+            app.cancel_attestation(0, c_sig.to_bytes().to_vec());
+        });
+
+        assert!(
+            check_error("agreement already completed!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_pos_after_sign_deadline() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation.sign_deadline = Some(100);
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let late_caller = update_states_at(Some(1), 100);
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Expired);
+
+        let mut pos = create_pos(&attestation, String::from(signatory.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &late_caller).to_bytes().to_vec();
+        let result = std::panic::catch_unwind(move || {
+            app.store_pos(pos);
+        });
+
+        assert!(
+            check_error("sign deadline passed.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_rejection() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        // 1 of 2 signatories
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        attestation.threshold = Some(SigningThreshold::Count(1));
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        let mut rejection = ProofOfRejection {
+            attestation_id: 0,
+            creator: String::from(signer.as_str()),
+            content_hash: app.get_attestation(0).content_hash(),
+            reason_hash: vec![1; 32],
+            created_at: 0,
+            signature: vec![],
+        };
+        let r_sig = sign_transaction(&rejection.to_ed25519_message(), &caller);
+        rejection.signature = r_sig.to_bytes().to_vec();
+        app.store_rejection(
+            0,
+            rejection.content_hash.clone(),
+            rejection.reason_hash.clone(),
+            rejection.signature.clone(),
+        );
+
+        assert_eq!(app.get_rejections(0), vec![rejection]);
+        // The other signatory can still form the agreement
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Draft);
+
+        let sec_caller = update_states(Some(1));
+        let mut sec_rejection = ProofOfRejection {
+            attestation_id: 0,
+            creator: String::from(signatory.as_str()),
+            content_hash: app.get_attestation(0).content_hash(),
+            reason_hash: vec![2; 32],
+            created_at: 0,
+            signature: vec![],
+        };
+        let sec_sig = sign_transaction(&sec_rejection.to_ed25519_message(), &sec_caller);
+        sec_rejection.signature = sec_sig.to_bytes().to_vec();
+        app.store_rejection(
+            0,
+            sec_rejection.content_hash,
+            sec_rejection.reason_hash,
+            sec_rejection.signature,
+        );
+
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Rejected);
+    }
+
+    #[test]
+    fn test_store_rejection_content_hash_mismatch() {
+        let caller = update_states(Some(0));
+
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+
+            let signer = String::from(accounts(0).as_str());
+            let mut schema = create_schema(signer.clone());
+            let message = schema.to_ed25519_message();
+            schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+            store_active_schema(&mut app, schema);
+
+            let mut attestation = create_attestation(signer.clone(), signer.clone());
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation.clone());
+
+            // The rejection refers to other content
+            let mut rejected = app.get_attestation(0);
+            rejected.attestation_result[1].value = vec![1, 0];
+            let rejection = ProofOfRejection {
+                attestation_id: 0,
+                creator: signer,
+                content_hash: rejected.content_hash(),
+                reason_hash: vec![1; 32],
+                created_at: 0,
+                signature: vec![],
+            };
+            let r_sig = sign_transaction(&rejection.to_ed25519_message(), &caller);
+            app.store_rejection(
+                0,
+                rejection.content_hash,
+                rejection.reason_hash,
+                r_sig.to_bytes().to_vec(),
+            );
+        });
+
+        assert!(
+            check_error("content hash mismatch.", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_store_pos_after_rejection() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let mut attestation = create_attestation(
+            String::from(signer.as_str()),
+            String::from(signatory.as_str()),
+        );
+        let a_mes = attestation.to_ed25519_message();
+        let a_sig = sign_transaction(&a_mes, &caller);
+        attestation.signature = a_sig.to_bytes().to_vec();
+        app.store_attestation(attestation.clone());
+
+        // The signatory rejects, every signatory is required
+        let sec_caller = update_states(Some(1));
+        let rejection = ProofOfRejection {
+            attestation_id: 0,
+            creator: String::from(signatory.as_str()),
+            content_hash: app.get_attestation(0).content_hash(),
+            reason_hash: vec![1; 32],
+            created_at: 0,
+            signature: vec![],
+        };
+        let r_sig = sign_transaction(&rejection.to_ed25519_message(), &sec_caller);
+        app.store_rejection(
+            0,
+            rejection.content_hash,
+            rejection.reason_hash,
+            r_sig.to_bytes().to_vec(),
+        );
+        assert_eq!(app.get_attestation_status(0), AttestationStatus::Rejected);
+
+        let caller = update_states(Some(0));
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        let result = std::panic::catch_unwind(move || {
+            app.store_pos(pos);
         });
 
         assert!(
-            check_error("empty schema definition!", result),
+            check_error("attestation rejected.", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_store_attestation() {
-        let caller = update_states(Some(0));
+    fn test_store_pos_content_hash() {
+        let caller = update_states_at(Some(0), 50);
 
         let mut app = create_daosign_app();
 
@@ -689,27 +4315,33 @@ mod test {
         let message = schema.to_ed25519_message();
         let signature = sign_transaction(&message, &caller);
         schema.signature = signature.to_bytes().to_vec();
-        // Store schema
-        app.store_schema(schema.clone());
+        store_active_schema(&mut app, schema.clone());
 
         let mut attestation = create_attestation(
             String::from(signer.as_str()),
             String::from(signatory.as_str()),
         );
-
         let a_mes = attestation.to_ed25519_message();
         let a_sig = sign_transaction(&a_mes, &caller);
         attestation.signature = a_sig.to_bytes().to_vec();
-
         app.store_attestation(attestation.clone());
 
-        // Verify schema is stored
-        assert_eq!(app.attestations.len(), 1);
-        assert_eq!(app.attestations.get(&0), Some(&attestation));
+        let caller = update_states_at(Some(0), 100);
+        let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+        pos.signed_at = 90;
+        let pos_mes = pos.to_ed25519_message();
+        pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+        app.store_pos(pos);
+
+        // Stored at block time
+        let stored = &app.get_proof_of_signature(0)[0];
+        assert_eq!(stored.created_at, 100);
+        assert_eq!(stored.signed_at, 90);
+        assert_eq!(stored.content_hash, attestation.content_hash());
     }
 
     #[test]
-    fn test_attestation_schema_does_not_exist() {
+    fn test_store_pos_content_hash_mismatch() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
@@ -718,64 +4350,123 @@ mod test {
             let signatory: AccountId = accounts(1);
             let signer: AccountId = accounts(0);
 
+            let mut schema = create_schema(String::from(signer.as_str()));
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            store_active_schema(&mut app, schema.clone());
+
             let mut attestation = create_attestation(
                 String::from(signer.as_str()),
                 String::from(signatory.as_str()),
             );
-
             let a_mes = attestation.to_ed25519_message();
             let a_sig = sign_transaction(&a_mes, &caller);
             attestation.signature = a_sig.to_bytes().to_vec();
-
             app.store_attestation(attestation.clone());
+
+            // Signed over different content
+            attestation.attestation_result[1].value = vec![1, 0];
+            let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+            app.store_pos(pos);
         });
 
         assert!(
-            check_error("called `Option::unwrap()` on a `None` value", result),
+            check_error("content hash mismatch.", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_attestation_unauthorized_attestator() {
+    fn test_store_pos_creator_not_signer() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
             let mut app = create_daosign_app();
 
             let signatory: AccountId = accounts(1);
-
             let signer: AccountId = accounts(0);
 
             let mut schema = create_schema(String::from(signer.as_str()));
             let message = schema.to_ed25519_message();
             let signature = sign_transaction(&message, &caller);
             schema.signature = signature.to_bytes().to_vec();
-            // Store schema
-            app.store_schema(schema.clone());
+            store_active_schema(&mut app, schema.clone());
 
             let mut attestation = create_attestation(
                 String::from(signer.as_str()),
                 String::from(signatory.as_str()),
             );
-
-            attestation.creator = "test.test".parse().expect(" invalid precompile data");
-
             let a_mes = attestation.to_ed25519_message();
             let a_sig = sign_transaction(&a_mes, &caller);
             attestation.signature = a_sig.to_bytes().to_vec();
-
             app.store_attestation(attestation.clone());
+
+            // The signatory claims the creator signed
+            let sec_caller = update_states(Some(1));
+            let mut pos = create_pos(&attestation, String::from(signer.as_str()));
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &sec_caller).to_bytes().to_vec();
+            app.store_pos(pos);
         });
 
         assert!(
-            check_error("unauthorized attestator!", result),
+            check_error("creator is not the signer.", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_attestation_schema_already_expired() {
+    fn test_store_attestations_batch() {
+        let caller = update_states(Some(0));
+
+        let mut app = create_daosign_app();
+
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let attestations: Vec<Attestation> = (0..3)
+            .map(|_| {
+                let mut attestation = create_attestation(
+                    String::from(signer.as_str()),
+                    String::from(signatory.as_str()),
+                );
+                let a_mes = attestation.to_ed25519_message();
+                attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+                attestation
+            })
+            .collect();
+
+        let ids = app.store_attestations_batch(attestations.clone());
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(app.attestations.len(), 3);
+
+        // The signer signs the whole cohort at once
+        let batch: Vec<ProofOfSignature> = ids
+            .iter()
+            .map(|id| {
+                let mut pos = create_pos(&app.get_attestation(*id), String::from(signer.as_str()));
+                let pos_mes = pos.to_ed25519_message();
+                pos.signature = sign_transaction(&pos_mes, &caller).to_bytes().to_vec();
+                pos
+            })
+            .collect();
+        assert_eq!(app.store_pos_batch(batch), vec![0, 1, 2]);
+        for id in ids {
+            assert_eq!(app.get_signers(id), vec![String::from(signer.as_str())]);
+        }
+    }
+
+    #[test]
+    fn test_store_attestations_batch_invalid_item() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
@@ -785,221 +4476,404 @@ mod test {
             let signer: AccountId = accounts(0);
 
             let mut schema = create_schema(String::from(signer.as_str()));
-            schema.metadata.expire_in = 1;
             let message = schema.to_ed25519_message();
             let signature = sign_transaction(&message, &caller);
             schema.signature = signature.to_bytes().to_vec();
-
-            // Store schema
-            app.store_schema(schema.clone());
+            store_active_schema(&mut app, schema.clone());
 
             let mut attestation = create_attestation(
                 String::from(signer.as_str()),
                 String::from(signatory.as_str()),
             );
             let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
 
-            app.store_attestation(attestation.clone());
+            // The second item isn't signed by its creator
+            let mut forged = attestation.clone();
+            forged.signature = vec![0; 64];
+
+            app.store_attestations_batch(vec![attestation, forged]);
         });
 
         assert!(
-            check_error("schema already expired!", result),
+            check_error("invalid signature", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_attestation_length_mismatch() {
-        let caller = update_states(Some(0));
+    fn test_store_attestations_batch_too_large() {
+        update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
             let mut app = create_daosign_app();
+            let attestation = create_attestation(
+                String::from(accounts(0).as_str()),
+                String::from(accounts(1).as_str()),
+            );
+            app.store_attestations_batch(vec![attestation; MAX_BATCH + 1]);
+        });
 
-            let signatory: AccountId = accounts(1);
-            let signer: AccountId = accounts(0);
+        assert!(
+            check_error("batch too large!", result),
+            "Unexpected error message"
+        );
+    }
 
-            let mut schema = create_schema(String::from(signer.as_str()));
-            let message = schema.to_ed25519_message();
-            let signature = sign_transaction(&message, &caller);
-            schema.signature = signature.to_bytes().to_vec();
+    // Signed attestations of the test schema from accounts(0) to accounts(1)
+    fn create_signed_attestations(caller: &Keypair, count: usize) -> Vec<Attestation> {
+        (0..count)
+            .map(|_| {
+                let mut attestation = create_attestation(
+                    String::from(accounts(0).as_str()),
+                    String::from(accounts(1).as_str()),
+                );
+                let a_mes = attestation.to_ed25519_message();
+                attestation.signature = sign_transaction(&a_mes, caller).to_bytes().to_vec();
+                attestation
+            })
+            .collect()
+    }
 
-            // Store schema
-            app.store_schema(schema.clone());
+    #[test]
+    fn test_store_attestations_batch_insufficient_deposit() {
+        let caller = update_states(Some(0));
 
-            let mut attestation = create_attestation(
-                String::from(signer.as_str()),
-                String::from(signatory.as_str()),
-            );
-            attestation.attestation_result.clear();
+        let mut app = create_daosign_app();
+        let mut schema = create_schema(String::from(accounts(0).as_str()));
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema);
 
-            let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
+        let attestations = create_signed_attestations(&caller, 2);
 
-            app.store_attestation(attestation.clone());
+        // One yocto short of the storage of two attestations
+        let cost = storage_cost(2 * attestation_storage_bytes(&attestations[0]));
+        attach_deposit(
+            0,
+            &caller,
+            cost.saturating_sub(NearToken::from_yoctonear(1)),
+        );
+
+        let result = std::panic::catch_unwind(move || {
+            app.store_attestations_batch(attestations);
         });
 
-        // Check that the error occurred and contains the expected message
         assert!(
-            check_error("attestation length mismatch!", result),
+            check_error("insufficient storage deposit!", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_attestation_name_mismatch() {
+    fn test_store_attestation_insufficient_deposit() {
         let caller = update_states(Some(0));
 
-        let result = std::panic::catch_unwind(|| {
-            let mut app = create_daosign_app();
+        let mut app = create_daosign_app();
+        let mut schema = create_schema(String::from(accounts(0).as_str()));
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema);
 
-            let signer: AccountId = accounts(0);
-            let signatory: AccountId = accounts(1);
+        // The single entry point charges the storage too
+        attach_deposit(0, &caller, NearToken::from_yoctonear(0));
+        let attestation = create_signed_attestations(&caller, 1).remove(0);
 
-            let mut schema = create_schema(String::from(signer.as_str()));
-            let message = schema.to_ed25519_message();
-            let signature = sign_transaction(&message, &caller);
-            schema.signature = signature.to_bytes().to_vec();
+        let result = std::panic::catch_unwind(move || {
+            app.store_attestation(attestation);
+        });
 
-            // Store schema
-            app.store_schema(schema.clone());
+        assert!(
+            check_error("insufficient storage deposit!", result),
+            "Unexpected error message"
+        );
+    }
 
-            let mut attestation = create_attestation(
-                String::from(signer.as_str()),
-                String::from(signatory.as_str()),
-            );
-            attestation.attestation_result[0].name = String::from("foo");
+    #[test]
+    fn test_store_attestations_batch_mint_gas() {
+        let caller = update_states(Some(0));
 
-            let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
+        let mut app = create_daosign_app();
+        let mut schema = create_schema(String::from(accounts(0).as_str()));
+        schema.signatory_policy.clear();
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema);
 
-            app.store_attestation(attestation.clone());
+        // Every attestation mints an nft to its recipient
+        let per_item = mint_gas(1).as_gas();
+        let max_items = (MAX_BATCH_MINT_GAS.as_gas() / per_item) as usize;
+        assert!(max_items < MAX_BATCH);
+
+        let ids = app.store_attestations_batch(create_signed_attestations(&caller, max_items));
+        assert_eq!(ids.len(), max_items);
+
+        let attestations = create_signed_attestations(&caller, max_items + 1);
+        let result = std::panic::catch_unwind(move || {
+            app.store_attestations_batch(attestations);
         });
 
-        // Check that the error occurred and contains the expected message
         assert!(
-            check_error("attestation name mismatch!", result),
+            check_error("batch mints exceed the gas limit!", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_attestation_type_mismatch() {
+    fn test_store_pos_batch_mint_gas() {
         let caller = update_states(Some(0));
 
-        let result = std::panic::catch_unwind(|| {
-            let mut app = create_daosign_app();
+        let mut app = create_daosign_app();
+        let mut schema = create_schema(String::from(accounts(0).as_str()));
+        schema.signatory_policy.clear();
+        schema.metadata.poa_mint_policy = PoaMintPolicy::Signatories;
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema);
+
+        // Every proof may complete an agreement minting an nft to both signatories
+        let per_item = mint_gas(2).as_gas();
+        let max_items = (MAX_BATCH_MINT_GAS.as_gas() / per_item) as usize;
+        let ids = app.store_attestations_batch(create_signed_attestations(&caller, max_items + 1));
+
+        let signatory = update_states(Some(1));
+        let proofs: Vec<ProofOfSignature> = ids
+            .iter()
+            .map(|id| {
+                let mut pos = create_pos(
+                    &app.get_attestation(*id),
+                    String::from(accounts(1).as_str()),
+                );
+                let pos_mes = pos.to_ed25519_message();
+                pos.signature = sign_transaction(&pos_mes, &signatory).to_bytes().to_vec();
+                pos
+            })
+            .collect();
 
-            let signer: AccountId = accounts(0);
-            let signatory: AccountId = accounts(1);
+        let result = std::panic::catch_unwind(move || {
+            app.store_pos_batch(proofs);
+        });
 
-            let mut schema = create_schema(String::from(signer.as_str()));
-            let message = schema.to_ed25519_message();
-            let signature = sign_transaction(&message, &caller);
-            schema.signature = signature.to_bytes().to_vec();
+        assert!(
+            check_error("batch mints exceed the gas limit!", result),
+            "Unexpected error message"
+        );
+    }
 
-            // Store schema
-            app.store_schema(schema.clone());
+    // Stores a bulk attestation of three attestations from accounts(0) to accounts(1)
+    fn store_bulk(app: &mut DAOSignApp, caller: &Keypair) -> (Vec<Attestation>, Vec<Vec<u8>>) {
+        let signer = String::from(accounts(0).as_str());
+        let holder = String::from(accounts(1).as_str());
 
-            let mut attestation = create_attestation(
-                String::from(signer.as_str()),
-                String::from(signatory.as_str()),
-            );
-            attestation.attestation_result[0].attestation_result_type = String::from("foo");
+        let mut schema = create_schema(signer.clone());
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, caller).to_bytes().to_vec();
+        store_active_schema(app, schema);
+
+        let attestations: Vec<Attestation> = (0..3)
+            .map(|i| {
+                let mut attestation = create_attestation(signer.clone(), holder.clone());
+                attestation.attestation_id = i;
+                attestation.recipient = holder.clone();
+                attestation.signature = vec![];
+                attestation
+            })
+            .collect();
+        let leaves: Vec<Vec<u8>> = attestations.iter().map(|a| a.leaf_hash()).collect();
 
-            let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
+        let mut bulk = BulkAttestation {
+            bulk_id: 0,
+            schema_id: 0,
+            creator: signer,
+            merkle_root: merkle::root(&leaves),
+            leaves: 3,
+            created_at: 1,
+            signature: vec![],
+        };
+        let message = bulk.to_ed25519_message();
+        bulk.signature = sign_transaction(&message, caller).to_bytes().to_vec();
+        assert_eq!(app.store_bulk_attestation(bulk), 0);
 
-            app.store_attestation(attestation.clone());
+        (attestations, leaves)
+    }
+
+    #[test]
+    fn test_store_bulk_attestation_insufficient_deposit() {
+        let caller = update_states(Some(0));
+        let signer = String::from(accounts(0).as_str());
+
+        let mut app = create_daosign_app();
+        let mut schema = create_schema(signer.clone());
+        let message = schema.to_ed25519_message();
+        schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+        store_active_schema(&mut app, schema);
+
+        let attestation = create_attestation(signer.clone(), String::from(accounts(1).as_str()));
+        let mut bulk = BulkAttestation {
+            bulk_id: 0,
+            schema_id: 0,
+            creator: signer,
+            merkle_root: merkle::root(&[attestation.leaf_hash()]),
+            leaves: 1,
+            created_at: 1,
+            signature: vec![],
+        };
+        let message = bulk.to_ed25519_message();
+        bulk.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+
+        // The root is charged for like any stored record
+        attach_deposit(0, &caller, NearToken::from_yoctonear(0));
+        let result = std::panic::catch_unwind(move || {
+            app.store_bulk_attestation(bulk);
         });
 
-        // Check that the error occurred and contains the expected message
         assert!(
-            check_error("attestation type mismatch!", result),
+            check_error("insufficient storage deposit!", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_store_revoke() {
+    fn test_claim_attestation() {
         let caller = update_states(Some(0));
-
         let mut app = create_daosign_app();
+        let (attestations, leaves) = store_bulk(&mut app, &caller);
+
+        // Nothing is stored until claimed
+        assert!(app.attestations.is_empty());
+        assert!(app.verify_bulk_inclusion(0, attestations[1].clone(), merkle::proof(&leaves, 1)));
+        assert!(!app.verify_bulk_inclusion(0, attestations[1].clone(), merkle::proof(&leaves, 0)));
+
+        // The recipient claims its attestation, a signature passed with it isn't kept
+        update_states(Some(1));
+        let mut claimed = attestations[1].clone();
+        claimed.signature = vec![1; 64];
+        let id = app.claim_attestation(0, claimed, merkle::proof(&leaves, 1));
+        assert_eq!(id, 0);
+        assert_eq!(app.get_attestation(id).recipient, accounts(1).to_string());
+        assert!(app.get_attestation(id).signature.is_empty());
+        assert_eq!(
+            app.get_claimed_attestation_id(0, attestations[1].clone()),
+            Some(id)
+        );
+        assert_eq!(
+            app.get_claimed_attestation_id(0, attestations[0].clone()),
+            None
+        );
+        assert_eq!(
+            app.user_attestation[&0][accounts(1).as_str()]
+                .iter()
+                .map(|a| a.attestation_id)
+                .collect::<Vec<u128>>(),
+            vec![id]
+        );
 
-        let signatory: AccountId = accounts(1);
+        // The attestation is signed like any other
+        let mut pos = create_pos(&app.get_attestation(id), String::from(accounts(1).as_str()));
+        let pos_mes = pos.to_ed25519_message();
+        let holder = update_states(Some(1));
+        pos.signature = sign_transaction(&pos_mes, &holder).to_bytes().to_vec();
+        app.store_pos(pos);
+        assert_eq!(app.get_signers(id), vec![accounts(1).to_string()]);
 
-        let signer: AccountId = accounts(0);
+        // A claim stores the attestation once
+        let result = std::panic::catch_unwind(move || {
+            app.claim_attestation(0, attestations[1].clone(), merkle::proof(&leaves, 1));
+        });
+        assert!(
+            check_error("attestation already claimed!", result),
+            "Unexpected error message"
+        );
+    }
 
-        let mut schema = create_schema(String::from(signer.as_str()));
-        let message = schema.to_ed25519_message();
-        let signature = sign_transaction(&message, &caller);
-        schema.signature = signature.to_bytes().to_vec();
+    #[test]
+    fn test_claim_attestation_invalid_proof() {
+        let caller = update_states(Some(0));
 
-        // Store schema
-        app.store_schema(schema.clone());
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            let (attestations, leaves) = store_bulk(&mut app, &caller);
 
-        let mut attestation = create_attestation(
-            String::from(signer.as_str()),
-            String::from(signatory.as_str()),
+            update_states(Some(1));
+            let mut altered = attestations[1].clone();
+            altered.attestation_result[1].value = vec![255, 255];
+            app.claim_attestation(0, altered, merkle::proof(&leaves, 1));
+        });
+
+        assert!(
+            check_error("invalid inclusion proof!", result),
+            "Unexpected error message"
         );
-        let a_mes = attestation.to_ed25519_message();
-        let a_sig = sign_transaction(&a_mes, &caller);
-        attestation.signature = a_sig.to_bytes().to_vec();
+    }
 
-        app.store_attestation(attestation.clone());
+    #[test]
+    fn test_claim_attestation_unauthorized() {
+        let caller = update_states(Some(0));
 
-        let r_mes = attestation.to_ed25519_message_revoke();
-        let r_sig = sign_transaction(&r_mes, &caller);
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
+            let (attestations, leaves) = store_bulk(&mut app, &caller);
 
-        attestation.revoke_signature = r_sig.to_bytes().to_vec();
+            update_states(Some(2));
+            app.claim_attestation(0, attestations[1].clone(), merkle::proof(&leaves, 1));
+        });
 
-        app.store_revoke(attestation.attestation_id, attestation.revoke_signature);
-        // Verify schema is stored
-        assert_eq!(app.attestations.len(), 1);
-        if let Some(a) = app.attestations.get(&0) {
-            // Assert that `is_revoked` is `true`
-            assert_eq!(a.is_revoked, true, "Attestation should be revoked");
-        } else {
-            panic!("Expected Some(attestation), but got None");
-        }
+        assert!(
+            check_error("unauthorized claim!", result),
+            "Unexpected error message"
+        );
     }
 
     #[test]
-    fn test_revoke_attestation_does_not_exist() {
+    fn test_find_attestations_by_document_hash() {
         let caller = update_states(Some(0));
 
-        let result = std::panic::catch_unwind(|| {
-            let mut app = create_daosign_app();
+        let mut app = create_daosign_app();
 
-            let caller = create_signer();
-            let signer: AccountId = accounts(0);
-            let signatory: AccountId = accounts(1);
+        let signatory: AccountId = accounts(1);
+        let signer: AccountId = accounts(0);
+
+        let mut schema = create_schema(String::from(signer.as_str()));
+        let message = schema.to_ed25519_message();
+        let signature = sign_transaction(&message, &caller);
+        schema.signature = signature.to_bytes().to_vec();
+        store_active_schema(&mut app, schema.clone());
+
+        let document = DocumentRef {
+            name: String::from("agreement.pdf"),
+            hash: vec![7; 32],
+            hash_algorithm: HashAlgorithm::Sha256,
+            location: Some(String::from(
+                "ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U",
+            )),
+        };
 
+        // The agreement and its amendment anchor the same document
+        for documents in [vec![document.clone()], vec![document.clone()], vec![]] {
             let mut attestation = create_attestation(
                 String::from(signer.as_str()),
                 String::from(signatory.as_str()),
             );
-            let r_mes = attestation.to_ed25519_message_revoke();
-            let r_sig = sign_transaction(&r_mes, &caller);
-
-            attestation.revoke_signature = r_sig.to_bytes().to_vec();
-
-            app.store_revoke(attestation.attestation_id, attestation.revoke_signature);
-        });
+            attestation.documents = documents;
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation);
+        }
 
-        // Check that the error occurred and contains the expected message
-        assert!(
-            check_error("called `Option::unwrap()` on a `None` value", result),
-            "Unexpected error message"
-        );
+        let found: Vec<u128> = app
+            .find_attestations_by_document_hash(vec![7; 32])
+            .iter()
+            .map(|a| a.attestation_id)
+            .collect();
+        assert_eq!(found, vec![0, 1]);
+        assert_eq!(app.get_attestation(0).documents, vec![document]);
+        assert!(app
+            .find_attestations_by_document_hash(vec![8; 32])
+            .is_empty());
     }
 
     #[test]
-    fn test_revoke_unauthorized_attestator() {
+    fn test_attestation_invalid_document_hash() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
@@ -1012,76 +4886,103 @@ mod test {
             let message = schema.to_ed25519_message();
             let signature = sign_transaction(&message, &caller);
             schema.signature = signature.to_bytes().to_vec();
-
-            // Store schema
-            app.store_schema(schema.clone());
+            store_active_schema(&mut app, schema.clone());
 
             let mut attestation = create_attestation(
-                String::from(signatory.as_str()),
                 String::from(signer.as_str()),
+                String::from(signatory.as_str()),
             );
+            attestation.documents = vec![DocumentRef {
+                name: String::from("agreement.pdf"),
+                hash: vec![7; 20],
+                hash_algorithm: HashAlgorithm::Keccak256,
+                location: None,
+            }];
             let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
-
-            app.store_attestation(attestation.clone());
-
-            let r_mes = attestation.to_ed25519_message_revoke();
-
-            let sec_caller = update_states(Some(1));
-            let r_sig = sign_transaction(&r_mes, &sec_caller);
-
-            attestation.revoke_signature = r_sig.to_bytes().to_vec();
-
-            app.store_revoke(attestation.attestation_id, attestation.revoke_signature);
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation);
         });
 
-        // Check that the error occurred and contains the expected message
         assert!(
-            check_error("unauthorized attestator!", result),
+            check_error("invalid document hash length!", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_store_pos_and() {
+    fn test_private_field_commitment() {
         let caller = update_states(Some(0));
 
         let mut app = create_daosign_app();
 
         let signatory: AccountId = accounts(1);
-
         let signer: AccountId = accounts(0);
 
         let mut schema = create_schema(String::from(signer.as_str()));
+        schema.schema_definition[1].privacy = FieldPrivacy::HashCommitment;
         let message = schema.to_ed25519_message();
         let signature = sign_transaction(&message, &caller);
         schema.signature = signature.to_bytes().to_vec();
-        // Store schema
-        app.store_schema(schema.clone());
+        store_active_schema(&mut app, schema.clone());
 
+        let salt = vec![7; 32];
         let mut attestation = create_attestation(
             String::from(signer.as_str()),
             String::from(signatory.as_str()),
         );
+        attestation.attestation_result[1].value = field_commitment(&salt, &[16, 0]);
         let a_mes = attestation.to_ed25519_message();
-        let a_sig = sign_transaction(&a_mes, &caller);
-        attestation.signature = a_sig.to_bytes().to_vec();
+        attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        app.store_attestation(attestation);
 
-        app.store_attestation(attestation.clone());
+        // Only the commitment is on chain and in the event
+        assert_eq!(
+            app.get_attestation(0).attestation_result[1].value,
+            field_commitment(&salt, &[16, 0])
+        );
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .all(|log| !log.contains("value: [16, 0]")));
 
-        let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
+        assert!(app.verify_field_reveal(0, String::from("salary"), salt.clone(), vec![16, 0]));
+        assert!(!app.verify_field_reveal(0, String::from("salary"), salt, vec![16, 1]));
+    }
 
-        let pos_mes = pos.to_ed25519_message();
-        let pos_sig = sign_transaction(&pos_mes, &caller);
+    #[test]
+    fn test_attestation_invalid_field_commitment() {
+        let caller = update_states(Some(0));
 
-        pos.signature = pos_sig.to_bytes().to_vec();
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
 
-        app.store_pos(pos);
+            let signatory: AccountId = accounts(1);
+            let signer: AccountId = accounts(0);
+
+            let mut schema = create_schema(String::from(signer.as_str()));
+            schema.schema_definition[1].privacy = FieldPrivacy::HashCommitment;
+            let message = schema.to_ed25519_message();
+            let signature = sign_transaction(&message, &caller);
+            schema.signature = signature.to_bytes().to_vec();
+            store_active_schema(&mut app, schema.clone());
+
+            // The salary is left in plaintext
+            let mut attestation = create_attestation(
+                String::from(signer.as_str()),
+                String::from(signatory.as_str()),
+            );
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation);
+        });
+
+        assert!(
+            check_error("invalid field commitment!", result),
+            "Unexpected error message"
+        );
     }
 
     #[test]
-    fn test_store_pos_or() {
+    fn test_verify_field_disclosure() {
         let caller = update_states(Some(0));
 
         let mut app = create_daosign_app();
@@ -1090,35 +4991,132 @@ mod test {
         let signer: AccountId = accounts(0);
 
         let mut schema = create_schema(String::from(signer.as_str()));
-        schema.signatory_policy[0].operator = 0x02;
         let message = schema.to_ed25519_message();
         let signature = sign_transaction(&message, &caller);
         schema.signature = signature.to_bytes().to_vec();
-        // Store schema
-        app.store_schema(schema.clone());
+        store_active_schema(&mut app, schema.clone());
 
+        // The fields are kept off-chain, only their commitments and root are signed
+        let fields = vec![
+            SaltedField {
+                name: String::from("vacancies"),
+                value: b"engineer".to_vec(),
+                salt: vec![1; 32],
+            },
+            SaltedField {
+                name: String::from("salary"),
+                value: vec![16, 0],
+                salt: vec![2; 32],
+            },
+        ];
         let mut attestation = create_attestation(
             String::from(signer.as_str()),
             String::from(signatory.as_str()),
         );
+        for (result, field) in attestation.attestation_result.iter_mut().zip(&fields) {
+            result.value = field.commitment();
+        }
+        attestation.fields_root = disclosure::fields_root(&fields);
         let a_mes = attestation.to_ed25519_message();
-        let a_sig = sign_transaction(&a_mes, &caller);
-        attestation.signature = a_sig.to_bytes().to_vec();
+        attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        app.store_attestation(attestation);
+
+        let vacancies = disclosure::disclose(&fields, "vacancies").unwrap();
+        assert!(app.verify_field_disclosure(
+            0,
+            vacancies.name.clone(),
+            vacancies.value.clone(),
+            vacancies.salt.clone(),
+            vacancies.proof.clone()
+        ));
+        assert!(!app.verify_field_disclosure(
+            0,
+            vacancies.name.clone(),
+            b"manager".to_vec(),
+            vacancies.salt.clone(),
+            vacancies.proof.clone()
+        ));
+
+        // A replaced credential no longer discloses its fields
+        app.superseded_by.insert(0, 1);
+        assert!(!app.verify_field_disclosure(
+            0,
+            vacancies.name.clone(),
+            vacancies.value.clone(),
+            vacancies.salt.clone(),
+            vacancies.proof.clone()
+        ));
+        app.superseded_by.remove(&0);
+
+        // Nor does a revoked one
+        let r_mes = app.get_attestation(0).to_ed25519_message_revoke();
+        let r_sig = sign_transaction(&r_mes, &caller).to_bytes().to_vec();
+        app.store_revoke(0, r_sig);
+        assert!(!app.verify_field_disclosure(
+            0,
+            vacancies.name,
+            vacancies.value,
+            vacancies.salt,
+            vacancies.proof
+        ));
+    }
 
-        app.store_attestation(attestation.clone());
+    #[test]
+    fn test_attestation_fields_root_mismatch() {
+        let caller = update_states(Some(0));
 
-        let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
+        let result = std::panic::catch_unwind(|| {
+            let mut app = create_daosign_app();
 
-        let pos_mes = pos.to_ed25519_message();
-        let pos_sig = sign_transaction(&pos_mes, &caller);
+            let signer = String::from(accounts(0).as_str());
+            let mut schema = create_schema(signer.clone());
+            let message = schema.to_ed25519_message();
+            schema.signature = sign_transaction(&message, &caller).to_bytes().to_vec();
+            store_active_schema(&mut app, schema);
 
-        pos.signature = pos_sig.to_bytes().to_vec();
+            // The signed root doesn't commit to the results of the attestation
+            let fields = vec![SaltedField {
+                name: String::from("vacancies"),
+                value: b"engineer".to_vec(),
+                salt: vec![1; 32],
+            }];
+            let mut attestation = create_attestation(signer, String::from(accounts(1).as_str()));
+            attestation.fields_root = disclosure::fields_root(&fields);
+            let a_mes = attestation.to_ed25519_message();
+            attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.store_attestation(attestation);
+        });
 
-        app.store_pos(pos);
+        assert!(
+            check_error("invalid fields root!", result),
+            "Unexpected error message"
+        );
+    }
+
+    // Stores an attestation from accounts(0) and signs it by accounts(0) & accounts(1)
+    fn store_signed_attestation(app: &mut DAOSignApp, mut attestation: Attestation) -> u128 {
+        let caller = update_states(Some(0));
+        let a_mes = attestation.to_ed25519_message();
+        attestation.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        let id = if attestation.supersedes.is_some() {
+            app.amend_attestation(attestation)
+        } else {
+            app.store_attestation(attestation);
+            app.attestation_id - 1
+        };
+
+        for i in [0, 1] {
+            let signer = update_states(Some(i));
+            let mut pos = create_pos(&app.get_attestation(id), accounts(i).to_string());
+            let pos_mes = pos.to_ed25519_message();
+            pos.signature = sign_transaction(&pos_mes, &signer).to_bytes().to_vec();
+            app.store_pos(pos);
+        }
+        id
     }
 
     #[test]
-    fn test_store_pos_not() {
+    fn test_amend_attestation() {
         let caller = update_states(Some(0));
 
         let mut app = create_daosign_app();
@@ -1127,83 +5125,71 @@ mod test {
         let signer: AccountId = accounts(0);
 
         let mut schema = create_schema(String::from(signer.as_str()));
-        schema.signatory_policy[0].required_schema_id.clear();
-        schema.signatory_policy[0].required_schema_id = [1].to_vec();
-        schema.signatory_policy[0].operator = 0x03;
         let message = schema.to_ed25519_message();
         let signature = sign_transaction(&message, &caller);
         schema.signature = signature.to_bytes().to_vec();
-        // Store schema
-        app.store_schema(schema.clone());
+        store_active_schema(&mut app, schema.clone());
 
-        let mut attestation = create_attestation(
+        let original = create_attestation(
             String::from(signer.as_str()),
             String::from(signatory.as_str()),
         );
-        let a_mes = attestation.to_ed25519_message();
-        let a_sig = sign_transaction(&a_mes, &caller);
-        attestation.signature = a_sig.to_bytes().to_vec();
-
-        app.store_attestation(attestation.clone());
-
-        let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
-
-        let pos_mes = pos.to_ed25519_message();
-        let pos_sig = sign_transaction(&pos_mes, &caller);
-
-        pos.signature = pos_sig.to_bytes().to_vec();
+        let original_id = store_signed_attestation(&mut app, original.clone());
+        assert_eq!(
+            app.get_attestation_status(original_id),
+            AttestationStatus::Completed
+        );
 
-        app.store_pos(pos);
-    }
+        // The raise is agreed by both signatories
+        let mut amendment = original.clone();
+        amendment.attestation_result[1].value = vec![32, 0];
+        amendment.supersedes = Some(original_id);
 
-    #[test]
-    fn test_pos_invalid_signatory_address() {
         let caller = update_states(Some(0));
+        let a_mes = amendment.to_ed25519_message();
+        amendment.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+        let amendment_id = app.amend_attestation(amendment);
+        assert_eq!(app.get_amendments(original_id), vec![amendment_id]);
+
+        // The original stays in effect until the amendment is agreed
+        assert_eq!(app.get_superseded_by(original_id), None);
+        assert_eq!(
+            app.get_attestation_status(original_id),
+            AttestationStatus::Completed
+        );
 
-        let result = std::panic::catch_unwind(|| {
-            let mut app = create_daosign_app();
-
-            let signatory: AccountId = accounts(1);
-            let signer: AccountId = accounts(0);
-
-            let mut schema = create_schema(String::from(signer.as_str()));
-            let message = schema.to_ed25519_message();
-            let signature = sign_transaction(&message, &caller);
-            schema.signature = signature.to_bytes().to_vec();
-            // Store schema
-            app.store_schema(schema.clone());
-
-            let mut attestation = create_attestation(
-                String::from(signer.as_str()),
-                String::from(signatory.as_str()),
-            );
-            let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
-
-            app.store_attestation(attestation.clone());
-
-            let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
-
-            let sec_caller = update_states(Some(1));
+        for i in [0, 1] {
+            let signer = update_states(Some(i));
+            let mut pos = create_pos(&app.get_attestation(amendment_id), accounts(i).to_string());
             let pos_mes = pos.to_ed25519_message();
-            let pos_sig = sign_transaction(&pos_mes, &sec_caller);
-
-            pos.signature = pos_sig.to_bytes().to_vec();
-            update_states(Some(2));
-
+            pos.signature = sign_transaction(&pos_mes, &signer).to_bytes().to_vec();
             app.store_pos(pos);
-        });
+        }
 
-        // Check that the error occurred and contains the expected message
-        assert!(
-            check_error("Invalid signatory address.", result),
-            "Unexpected error message"
+        assert_eq!(app.get_superseded_by(original_id), Some(amendment_id));
+        assert_eq!(
+            app.get_attestation_status(original_id),
+            AttestationStatus::Superseded
         );
+        for id in [original_id, amendment_id] {
+            assert_eq!(
+                app.get_amendment_chain(id)
+                    .iter()
+                    .map(|a| a.attestation_id)
+                    .collect::<Vec<u128>>(),
+                vec![original_id, amendment_id]
+            );
+        }
+
+        // The salary slip refers to the amended agreement
+        let mut slip = original.clone();
+        slip.refers_to = Some(amendment_id);
+        let slip_id = store_signed_attestation(&mut app, slip);
+        assert_eq!(app.get_referring_attestations(amendment_id), vec![slip_id]);
     }
 
     #[test]
-    fn test_pos_unsupported_operator() {
+    fn test_amend_superseded_attestation() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
@@ -1213,42 +5199,33 @@ mod test {
             let signer: AccountId = accounts(0);
 
             let mut schema = create_schema(String::from(signer.as_str()));
-            schema.signatory_policy[0].operator = 0x04;
             let message = schema.to_ed25519_message();
             let signature = sign_transaction(&message, &caller);
             schema.signature = signature.to_bytes().to_vec();
-            // Store schema
-            app.store_schema(schema.clone());
+            store_active_schema(&mut app, schema.clone());
 
-            let mut attestation = create_attestation(
+            let original = create_attestation(
                 String::from(signer.as_str()),
                 String::from(signatory.as_str()),
             );
-            let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
-
-            app.store_attestation(attestation.clone());
-
-            let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
-
-            let pos_mes = pos.to_ed25519_message();
-            let pos_sig = sign_transaction(&pos_mes, &caller);
+            store_signed_attestation(&mut app, original.clone());
 
-            pos.signature = pos_sig.to_bytes().to_vec();
+            let mut amendment = original.clone();
+            amendment.supersedes = Some(0);
+            store_signed_attestation(&mut app, amendment.clone());
 
-            app.store_pos(pos);
+            // The original was already replaced
+            store_signed_attestation(&mut app, amendment);
         });
 
-        // Check that the error occurred and contains the expected message
         assert!(
-            check_error("Unsupported operator", result),
+            check_error("attestation already superseded!", result),
             "Unexpected error message"
         );
     }
 
     #[test]
-    fn test_pos_insufficient_attestations() {
+    fn test_amend_attestation_signatories_mismatch() {
         let caller = update_states(Some(0));
 
         let result = std::panic::catch_unwind(|| {
@@ -1258,85 +5235,30 @@ mod test {
             let signer: AccountId = accounts(0);
 
             let mut schema = create_schema(String::from(signer.as_str()));
-            schema.signatory_policy[0].required_schema_id.push(1);
             let message = schema.to_ed25519_message();
             let signature = sign_transaction(&message, &caller);
             schema.signature = signature.to_bytes().to_vec();
-            // Store schema
-            app.store_schema(schema.clone());
+            store_active_schema(&mut app, schema.clone());
 
-            let mut attestation = create_attestation(
+            let original = create_attestation(
                 String::from(signer.as_str()),
                 String::from(signatory.as_str()),
             );
-            let a_mes = attestation.to_ed25519_message();
-            let a_sig = sign_transaction(&a_mes, &caller);
-            attestation.signature = a_sig.to_bytes().to_vec();
-
-            app.store_attestation(attestation.clone());
-
-            let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
-
-            let pos_mes = pos.to_ed25519_message();
-            let pos_sig = sign_transaction(&pos_mes, &caller);
-
-            pos.signature = pos_sig.to_bytes().to_vec();
-
-            app.store_pos(pos);
+            store_signed_attestation(&mut app, original.clone());
+
+            // accounts(1) is left out of the amendment
+            let mut amendment =
+                create_attestation(String::from(signer.as_str()), accounts(2).to_string());
+            amendment.supersedes = Some(0);
+            let caller = update_states(Some(0));
+            let a_mes = amendment.to_ed25519_message();
+            amendment.signature = sign_transaction(&a_mes, &caller).to_bytes().to_vec();
+            app.amend_attestation(amendment);
         });
-        // Check that the error occurred and contains the expected message
+
         assert!(
-            check_error("insufficient attestations.", result),
+            check_error("amendment signatories mismatch!", result),
             "Unexpected error message"
         );
     }
-
-    #[test]
-    fn test_store_poa_two_signatory() {
-        let caller = update_states(Some(0));
-
-        let mut app = create_daosign_app();
-
-        let signatory: AccountId = accounts(1);
-
-        let signer: AccountId = accounts(0);
-
-        let mut schema = create_schema(String::from(signer.as_str()));
-        let message = schema.to_ed25519_message();
-        let signature = sign_transaction(&message, &caller);
-        schema.signature = signature.to_bytes().to_vec();
-
-        // Store schema
-        app.store_schema(schema.clone());
-
-        let mut attestation = create_attestation(
-            String::from(signer.as_str()),
-            String::from(signatory.as_str()),
-        );
-        let a_mes = attestation.to_ed25519_message();
-        let a_sig = sign_transaction(&a_mes, &caller);
-        attestation.signature = a_sig.to_bytes().to_vec();
-
-        // Store attestation
-        app.store_attestation(attestation.clone());
-
-        let mut pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
-        let pos_mes = pos.to_ed25519_message();
-        let pos_sig = sign_transaction(&pos_mes, &caller);
-
-        pos.signature = pos_sig.to_bytes().to_vec();
-
-        app.store_pos(pos);
-
-        let sec_caller = update_states(Some(1));
-
-        let mut sec_pos = create_pos(attestation.attestation_id, String::from(signer.as_str()));
-        let sec_pos_mes = sec_pos.to_ed25519_message();
-        let sec_pos_sig = sign_transaction(&sec_pos_mes, &sec_caller);
-
-        sec_pos.signature = sec_pos_sig.to_bytes().to_vec();
-
-        app.store_pos(sec_pos);
-        assert!(app.poa.len() == 1);
-    }
 }
@@ -0,0 +1,175 @@
+//! Selective disclosure of attestation fields.
+//!
+//! Every result of the attestation holds the commitment `field_commitment(salt, value)` of its
+//! field, and the attestation signs the Merkle root of these commitments in `fields_root`. A holder
+//! discloses one field by sharing its value, salt and Merkle path, the other fields stay hidden.
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{field_commitment, merkle, AttestationResult, MIN_SALT_LEN};
+
+/// Field value with the salt hiding it in the fields tree.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct SaltedField {
+    pub name: String,
+    pub value: Vec<u8>,
+    pub salt: Vec<u8>,
+}
+
+/// Field disclosed with its path up to the `fields_root` of the attestation.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct FieldDisclosure {
+    pub name: String,
+    pub value: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// Committed field as hashed into the fields tree.
+#[derive(Serialize)]
+struct FieldLeaf<'a> {
+    name: &'a str,
+    commitment: &'a [u8],
+}
+
+impl SaltedField {
+    /// Commitment stored as the value of the field in `attestation_result`.
+    pub fn commitment(&self) -> Vec<u8> {
+        field_commitment(&self.salt, &self.value)
+    }
+
+    /// Leaf of the field in the fields tree.
+    pub fn leaf_hash(&self) -> Vec<u8> {
+        commitment_leaf(&self.name, &self.commitment())
+    }
+}
+
+impl FieldDisclosure {
+    /// Checks the disclosed field against the root it was disclosed from.
+    pub fn verify(&self, fields_root: &[u8]) -> bool {
+        let field = SaltedField {
+            name: self.name.clone(),
+            value: self.value.clone(),
+            salt: self.salt.clone(),
+        };
+        self.salt.len() >= MIN_SALT_LEN
+            && merkle::verify(fields_root, &field.leaf_hash(), &self.proof)
+    }
+}
+
+/// Leaf of the field `name` committed to `commitment`.
+pub fn commitment_leaf(name: &str, commitment: &[u8]) -> Vec<u8> {
+    let leaf = FieldLeaf { name, commitment };
+    let message = serde_json::to_vec(&leaf).expect("Failed to serialize field");
+    merkle::leaf_hash(&message)
+}
+
+/// Root of the fields tree, leaves follow the order of `fields`.
+pub fn fields_root(fields: &[SaltedField]) -> Vec<u8> {
+    let leaves: Vec<Vec<u8>> = fields.iter().map(SaltedField::leaf_hash).collect();
+    merkle::root(&leaves)
+}
+
+/// Root of the fields tree of committed results, the `fields_root` they are signed under.
+pub fn results_root(results: &[AttestationResult]) -> Vec<u8> {
+    let leaves: Vec<Vec<u8>> = results
+        .iter()
+        .map(|r| commitment_leaf(&r.name, &r.value))
+        .collect();
+    merkle::root(&leaves)
+}
+
+/// Discloses the field `name` of `fields`.
+pub fn disclose(fields: &[SaltedField], name: &str) -> Option<FieldDisclosure> {
+    let index = fields.iter().position(|f| f.name == name)?;
+    let leaves: Vec<Vec<u8>> = fields.iter().map(SaltedField::leaf_hash).collect();
+    let field = &fields[index];
+    Some(FieldDisclosure {
+        name: field.name.clone(),
+        value: field.value.clone(),
+        salt: field.salt.clone(),
+        proof: merkle::proof(&leaves, index),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_fields() -> Vec<SaltedField> {
+        vec![
+            SaltedField {
+                name: String::from("employer"),
+                value: b"acme.testnet".to_vec(),
+                salt: vec![1; 32],
+            },
+            SaltedField {
+                name: String::from("position"),
+                value: b"engineer".to_vec(),
+                salt: vec![2; 32],
+            },
+            SaltedField {
+                name: String::from("salary"),
+                value: vec![16, 0],
+                salt: vec![3; 32],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_disclose_field() {
+        let fields = create_fields();
+        let root = fields_root(&fields);
+
+        for field in &fields {
+            assert!(disclose(&fields, &field.name).unwrap().verify(&root));
+        }
+        assert!(disclose(&fields, "bonus").is_none());
+
+        // The root follows from the committed results
+        let results: Vec<AttestationResult> = fields
+            .iter()
+            .map(|f| AttestationResult {
+                attestation_result_type: String::from("string"),
+                name: f.name.clone(),
+                value: f.commitment(),
+            })
+            .collect();
+        assert_eq!(results_root(&results), root);
+
+        // Another value or salt doesn't match the root
+        let mut disclosure = disclose(&fields, "employer").unwrap();
+        disclosure.value = b"other.testnet".to_vec();
+        assert!(!disclosure.verify(&root));
+
+        let mut disclosure = disclose(&fields, "employer").unwrap();
+        disclosure.salt = vec![2; 32];
+        assert!(!disclosure.verify(&root));
+
+        // The value of another field doesn't match either
+        let mut disclosure = disclose(&fields, "salary").unwrap();
+        disclosure.name = String::from("position");
+        assert!(!disclosure.verify(&root));
+    }
+}
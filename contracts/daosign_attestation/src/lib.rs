@@ -1,13 +1,24 @@
 use daosign_ed25519::recover;
-use daosign_schema::Schema;
+use daosign_schema::{AttestationRequirement, FieldPrivacy, Schema, SignatoryPolicy};
 use ed25519_dalek::{PublicKey, Signature};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    env,
+    env, AccountId,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use sha2::{Digest, Sha256};
+
+pub mod disclosure;
+pub mod merkle;
+
+/// Maximal number of signatories of an attestation.
+pub const MAX_SIGNATORIES: usize = 32;
+/// Maximal number of documents of an attestation.
+pub const MAX_DOCUMENTS: usize = 16;
+/// Minimal length of the salt of a field commitment, shorter salts let values be guessed.
+pub const MIN_SALT_LEN: usize = 16;
 
 /// ProofOfSignature struct representing the Proof-of-Signature parameters.
 // #[near_bindgen]
@@ -34,8 +45,41 @@ pub struct Attestation {
     pub is_revoked: bool,
     pub revoked_at: u64,
     pub revoke_signature: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatory_roles: Vec<SignatoryRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<SigningThreshold>, // every signatory must sign if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_order: Option<SigningOrder>, // signatories sign in any order if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign_deadline: Option<u64>, // block timestamp after which signatures are rejected
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_cancelled: bool,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cancelled_at: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cancel_signature: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub documents: Vec<DocumentRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields_root: Vec<u8>, // root of the committed results the recipient can disclose one by one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refers_to: Option<u128>, // attestation this one is linked to, e.g. the agreement of an invoice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supersedes: Option<u128>, // attestation this one amends once agreed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub stored_at: u64, // block timestamp the contract stored the attestation at
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
 }
 
+/// Signed content of an attestation. The contract assigns the id on storage, so it is left out.
 #[derive(
     BorshDeserialize,
     BorshSerialize,
@@ -48,13 +92,202 @@ pub struct Attestation {
     JsonSchema,
 )]
 pub struct AttestationMessage {
-    attestation_id: u128,
     schema_id: u128,
     attestation_result: Vec<AttestationResult>,
     creator: String,
     recipient: String,
     created_at: u64, // Use String to represent address
     signatories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    signatory_roles: Vec<SignatoryRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    threshold: Option<SigningThreshold>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signing_order: Option<SigningOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sign_deadline: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    documents: Vec<DocumentRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fields_root: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refers_to: Option<u128>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    supersedes: Option<u128>,
+}
+
+/// Lifecycle state of an attestation.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub enum AttestationStatus {
+    /// Stored, nobody signed yet.
+    Draft,
+    /// Signed by some signatories, the Proof-of-Agreement is not formed yet.
+    Pending,
+    /// The Proof-of-Agreement is formed.
+    Completed,
+    /// Withdrawn by the creator before completion.
+    Cancelled,
+    /// The signing deadline passed before completion.
+    Expired,
+    /// Rejections left the signing threshold out of reach.
+    Rejected,
+    Revoked,
+    /// An amendment agreed by the signatories replaced it.
+    Superseded,
+}
+
+/// Order in which the signatories sign.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningOrder {
+    /// One by one, in the order of `signatories`.
+    Sequential,
+    /// Stage by stage, signatories of the same stage sign in any order.
+    Stages(Vec<Vec<String>>),
+}
+
+/// Signatures required to form the Proof-of-Agreement.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningThreshold {
+    /// At least `m` signatories sign.
+    Count(u32),
+    /// The weight of the signatories who signed reaches `quorum`, `weights` follow `signatories`.
+    Weighted { weights: Vec<u64>, quorum: u64 },
+}
+
+/// Off-chain document an attestation is about, e.g. the PDF of the agreement.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct DocumentRef {
+    pub name: String,
+    pub hash: Vec<u8>,
+    pub hash_algorithm: HashAlgorithm,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>, // IPFS CID, Arweave tx or URL of the document
+}
+
+/// Algorithm of a document hash.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+    Keccak256,
+    Blake2b256,
+}
+
+impl HashAlgorithm {
+    /// Length of the hashes produced by the algorithm, in bytes.
+    pub fn hash_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 | HashAlgorithm::Keccak256 | HashAlgorithm::Blake2b256 => 32,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+/// Binds a signatory to the schema's signatory policy of its role.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct SignatoryRole {
+    pub signatory: String,
+    pub policy: PolicyRef,
+}
+
+/// Reference to a signatory policy of the schema.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRef {
+    /// Position in `signatory_policy`.
+    Index(u32),
+    /// `signatory_description` of the policy, e.g. "Employer".
+    Role(String),
+}
+
+impl PolicyRef {
+    /// Index of the referenced policy in the schema, if it exists.
+    pub fn resolve(&self, s: &Schema) -> Option<usize> {
+        match self {
+            PolicyRef::Index(i) => {
+                let i = *i as usize;
+                (i < s.signatory_policy.len()).then_some(i)
+            }
+            PolicyRef::Role(role) => s
+                .signatory_policy
+                .iter()
+                .position(|p| &p.signatory_description == role),
+        }
+    }
 }
 /// ProofOfSignature struct representing the Proof-of-Signature parameters.
 // #[near_bindgen]
@@ -75,6 +308,63 @@ pub struct AttestationResult {
     pub value: Vec<u8>,
 }
 
+/// Value of an `Encrypted` field, JSON encoded in `AttestationResult::value`. The field value is
+/// encrypted with a random content key, which is wrapped for each account allowed to read it.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct EncryptedValue {
+    pub algorithm: String, // e.g. "x25519-xsalsa20-poly1305"
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub keys: Vec<WrappedKey>,
+}
+
+/// Content key of an `EncryptedValue` wrapped for one reader.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct WrappedKey {
+    pub account_id: String,
+    pub public_key: Vec<u8>, // ed25519 key of the reader the content key is wrapped to
+    pub ephemeral_public_key: Vec<u8>,
+    pub wrapped_key: Vec<u8>,
+}
+
+impl EncryptedValue {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Failed to serialize encrypted value")
+    }
+
+    pub fn decode(value: &[u8]) -> Option<Self> {
+        serde_json::from_slice(value).ok()
+    }
+}
+
+/// Salted hash committing to the value of a `HashCommitment` field.
+pub fn field_commitment(salt: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(value);
+    hasher.finalize().to_vec()
+}
+
 #[derive(
     BorshDeserialize,
     BorshSerialize,
@@ -90,21 +380,144 @@ pub struct RevokeMessage {
     attestation_id: u128,
 }
 
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct CancelMessage {
+    attestation_id: u128,
+    action: String,
+}
+
+/// Merkle root of many attestations of a schema, signed once by their creator. The leaves are the
+/// `leaf_hash` of each attestation, holders claim their attestation with an inclusion proof.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct BulkAttestation {
+    pub bulk_id: u128,
+    pub schema_id: u128,
+    pub creator: String,
+    pub merkle_root: Vec<u8>,
+    pub leaves: u64,
+    pub created_at: u64,
+    pub signature: Vec<u8>,
+}
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct BulkAttestationMessage {
+    schema_id: u128,
+    creator: String,
+    merkle_root: Vec<u8>,
+    leaves: u64,
+    created_at: u64,
+}
+
+impl BulkAttestation {
+    pub fn to_ed25519_message(&self) -> Vec<u8> {
+        let bulk = BulkAttestationMessage {
+            schema_id: self.schema_id,
+            creator: self.creator.clone(),
+            merkle_root: self.merkle_root.clone(),
+            leaves: self.leaves,
+            created_at: self.created_at,
+        };
+        // Serialize the message to JSON and convert to bytes
+        serde_json::to_vec(&bulk).expect("Failed to serialize message")
+    }
+
+    /// Checks that `a` is one of the attestations under the root.
+    pub fn includes(&self, a: &Attestation, proof: &[Vec<u8>]) -> bool {
+        a.schema_id == self.schema_id
+            && a.creator == self.creator
+            && merkle::verify(&self.merkle_root, &a.leaf_hash(), proof)
+    }
+
+    pub fn validate(&self, s: &Schema) {
+        // Ensure that if the schema is private, the sender is the creator of the attestations.
+        if !s.metadata.is_public {
+            assert!(
+                self.creator == env::signer_account_id(),
+                "unauthorized attestator!"
+            );
+        }
+        assert!(
+            self.creator.parse::<AccountId>().is_ok(),
+            "invalid creator account!"
+        );
+        assert!(
+            self.merkle_root.len() == merkle::HASH_LEN,
+            "invalid merkle root!"
+        );
+        assert!(self.leaves > 0, "no attestations!");
+
+        let signature = Signature::from_bytes(&self.signature).expect("Invalid signature");
+        let caller_pk = env::signer_account_pk();
+        let caller = PublicKey::from_bytes(&caller_pk.as_bytes()[1..]).expect("Invalid public key");
+        assert!(
+            recover(caller, signature, &self.to_ed25519_message()),
+            "invalid signature"
+        );
+    }
+}
+
 impl Attestation {
     pub fn to_ed25519_message(&self) -> Vec<u8> {
         let attestaion = AttestationMessage {
-            attestation_id: self.attestation_id,
             schema_id: self.schema_id,
             attestation_result: self.attestation_result.clone(),
             creator: self.creator.clone(),
             recipient: self.recipient.clone(),
             created_at: self.created_at,
             signatories: self.signatories.clone(),
+            signatory_roles: self.signatory_roles.clone(),
+            threshold: self.threshold.clone(),
+            signing_order: self.signing_order.clone(),
+            sign_deadline: self.sign_deadline,
+            documents: self.documents.clone(),
+            fields_root: self.fields_root.clone(),
+            refers_to: self.refers_to,
+            supersedes: self.supersedes,
         };
         // Serialize the message to JSON and convert to bytes
         serde_json::to_vec(&attestaion).expect("Failed to serialize message") // directly return the serialized vector
     }
 
+    /// sha256 hash of the signed attestation message, Proofs of Signature commit to it.
+    pub fn content_hash(&self) -> Vec<u8> {
+        Sha256::digest(self.to_ed25519_message()).to_vec()
+    }
+
+    /// Merkle leaf of the attestation in a `BulkAttestation`.
+    pub fn leaf_hash(&self) -> Vec<u8> {
+        merkle::leaf_hash(&self.to_ed25519_message())
+    }
+
     pub fn to_ed25519_message_revoke(&self) -> Vec<u8> {
         let revoke = RevokeMessage {
             attestation_id: self.attestation_id,
@@ -113,6 +526,15 @@ impl Attestation {
         serde_json::to_vec(&revoke).expect("Failed to serialize message") // directly return the serialized vector
     }
 
+    pub fn to_ed25519_message_cancel(&self) -> Vec<u8> {
+        let cancel = CancelMessage {
+            attestation_id: self.attestation_id,
+            action: String::from("cancel"),
+        };
+        // Serialize the message to JSON and convert to bytes
+        serde_json::to_vec(&cancel).expect("Failed to serialize message") // directly return the serialized vector
+    }
+
     pub fn validate(&self, s: Schema) {
         // Ensure that if the schema is private, the sender is the creator of the attestation.
         if !s.metadata.is_public {
@@ -122,41 +544,448 @@ impl Attestation {
             );
         }
 
-        // Get the current block timestamp in seconds
-        let current_timestamp = env::block_timestamp();
+        self.validate_content(&s);
+
+        let signature = Signature::from_bytes(&self.signature).expect("Invalid signature");
+
+        let caller_pk = env::signer_account_pk(); // ✅ Extract raw bytes from `near_sdk::PublicKey`
+
+        // ✅ Ensure it's 33 bytes and remove the first byte (prefix)
+        let ed25519_bytes = &caller_pk.as_bytes()[1..]; // Extract only the last 32 bytes
+        let caller =
+            PublicKey::from_bytes(ed25519_bytes).expect("❌ Failed to parse Dalek PublicKey");
+        //Check signature
+        assert!(
+            recover(caller, signature, &self.to_ed25519_message()),
+            "invalid signature"
+        );
+    }
+
+    /// Checks the attestation content against the schema: results, signatories, signing rules
+    /// and the sign deadline. Does not check who submits it nor its signature.
+    pub fn validate_content(&self, s: &Schema) {
+        // Get the current block timestamp in seconds
+        let current_timestamp = env::block_timestamp();
+
+        // Check if the schema has expired based on its metadata
+        if s.metadata.expire_in != 0 {
+            assert!(
+                (s.metadata.created_at + s.metadata.expire_in
+                    < current_timestamp.try_into().unwrap()),
+                "schema already expired!"
+            );
+        }
+
+        // Ensure that the length of attestation results matches the length of the schema definition
+        assert!(
+            self.attestation_result.len() == s.schema_definition.len(),
+            "attestation length mismatch!"
+        );
+
+        let mut i = 0;
+        while i < s.schema_definition.len() {
+            // Check that the names match between the schema definition and the attestation result
+            assert!(
+                s.schema_definition[i].definition_name == self.attestation_result[i].name,
+                "attestation name mismatch!"
+            );
+            // Check that the types match between the schema definition and the attestation result
+            assert!(
+                s.schema_definition[i].definition_type
+                    == self.attestation_result[i].attestation_result_type,
+                "attestation type mismatch!"
+            );
+            self.validate_private_field(
+                s.schema_definition[i].privacy,
+                &self.attestation_result[i].value,
+            );
+            i += 1;
+        }
+
+        self.validate_signatories(s);
+        self.validate_signatory_roles(s);
+        self.validate_threshold();
+        self.validate_signing_order();
+        self.validate_documents();
+
+        // Disclosable results are commitments, signed under their root
+        assert!(
+            self.fields_root.is_empty()
+                || self.fields_root == disclosure::results_root(&self.attestation_result),
+            "invalid fields root!"
+        );
+
+        if let Some(deadline) = self.sign_deadline {
+            assert!(
+                deadline > current_timestamp,
+                "sign deadline already passed!"
+            );
+        }
+    }
+
+    /// Checks that the value of a private field is a commitment, or a value encrypted to the
+    /// recipient and every signatory.
+    pub fn validate_private_field(&self, privacy: FieldPrivacy, value: &[u8]) {
+        match privacy {
+            FieldPrivacy::Plaintext => {}
+            FieldPrivacy::HashCommitment => {
+                assert!(value.len() == 32, "invalid field commitment!");
+            }
+            FieldPrivacy::Encrypted => {
+                let encrypted = EncryptedValue::decode(value).expect("invalid encrypted field!");
+                assert!(!encrypted.ciphertext.is_empty(), "invalid encrypted field!");
+                let readers = self
+                    .signatories
+                    .iter()
+                    .chain(Some(&self.recipient).filter(|r| !r.is_empty()));
+                for reader in readers {
+                    assert!(
+                        encrypted.keys.iter().any(|k| &k.account_id == reader),
+                        "missing field key!"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks a value revealed off-chain against the commitment of a `HashCommitment` field.
+    pub fn verify_field_reveal(&self, s: &Schema, name: &str, salt: &[u8], value: &[u8]) -> bool {
+        let Some(i) = s
+            .schema_definition
+            .iter()
+            .position(|d| d.definition_name == name)
+        else {
+            return false;
+        };
+        s.schema_definition[i].privacy == FieldPrivacy::HashCommitment
+            && salt.len() >= MIN_SALT_LEN
+            && self
+                .attestation_result
+                .get(i)
+                .is_some_and(|r| r.name == name && r.value == field_commitment(salt, value))
+    }
+
+    /// Checks a field disclosed off-chain against `fields_root`.
+    pub fn verify_field_disclosure(&self, disclosure: &disclosure::FieldDisclosure) -> bool {
+        !self.fields_root.is_empty() && disclosure.verify(&self.fields_root)
+    }
+
+    /// Copy of the attestation safe to emit in events: encrypted values are replaced by their
+    /// sha256 hash, commitments are kept as they are.
+    pub fn redacted(&self, s: &Schema) -> Attestation {
+        let mut a = self.clone();
+        for (result, definition) in a.attestation_result.iter_mut().zip(&s.schema_definition) {
+            if definition.privacy == FieldPrivacy::Encrypted {
+                result.value = Sha256::digest(&result.value).to_vec();
+            }
+        }
+        a
+    }
+
+    /// Checks that every document hash has the length of its algorithm and is listed once.
+    pub fn validate_documents(&self) {
+        assert!(self.documents.len() <= MAX_DOCUMENTS, "too many documents!");
+        for (i, document) in self.documents.iter().enumerate() {
+            assert!(
+                document.hash.len() == document.hash_algorithm.hash_len(),
+                "invalid document hash length!"
+            );
+            assert!(
+                !self.documents[..i].iter().any(|d| d.hash == document.hash),
+                "duplicate document!"
+            );
+        }
+    }
+
+    /// Checks the creator, recipient and signatory accounts. Signatories are unique and, unless
+    /// they sign sequentially, listed in ascending order. Schemas with signatory policies expect a
+    /// Proof-of-Agreement, so their attestations need at least one signatory.
+    pub fn validate_signatories(&self, s: &Schema) {
+        assert!(
+            self.creator.parse::<AccountId>().is_ok(),
+            "invalid creator account!"
+        );
+        // An empty recipient means the attestation has none
+        assert!(
+            self.recipient.is_empty() || self.recipient.parse::<AccountId>().is_ok(),
+            "invalid recipient account!"
+        );
+
+        assert!(
+            s.signatory_policy.is_empty() || !self.signatories.is_empty(),
+            "no signatories!"
+        );
+        assert!(
+            self.signatories.len() <= MAX_SIGNATORIES,
+            "too many signatories!"
+        );
+
+        for (i, signatory) in self.signatories.iter().enumerate() {
+            assert!(
+                signatory.parse::<AccountId>().is_ok(),
+                "invalid signatory account!"
+            );
+            assert!(
+                !self.signatories[..i].contains(signatory),
+                "duplicate signatory!"
+            );
+        }
+
+        if self.signing_order != Some(SigningOrder::Sequential) {
+            assert!(
+                self.signatories.windows(2).all(|pair| pair[0] < pair[1]),
+                "signatories not in canonical order!"
+            );
+        }
+    }
+
+    /// Checks that every role binds a listed signatory, at most once, to an existing policy.
+    pub fn validate_signatory_roles(&self, s: &Schema) {
+        for (i, role) in self.signatory_roles.iter().enumerate() {
+            assert!(
+                self.signatories.contains(&role.signatory),
+                "unknown role signatory!"
+            );
+            assert!(
+                !self.signatory_roles[..i]
+                    .iter()
+                    .any(|r| r.signatory == role.signatory),
+                "duplicate signatory role!"
+            );
+            assert!(
+                role.policy.resolve(s).is_some(),
+                "unknown signatory policy!"
+            );
+        }
+    }
+
+    /// Checks that the signing threshold can be reached by the signatories.
+    pub fn validate_threshold(&self) {
+        match &self.threshold {
+            Some(SigningThreshold::Count(m)) => {
+                assert!(
+                    *m >= 1 && *m as usize <= self.signatories.len(),
+                    "invalid signing threshold!"
+                );
+            }
+            Some(SigningThreshold::Weighted { weights, quorum }) => {
+                assert!(
+                    weights.len() == self.signatories.len(),
+                    "signatory weights mismatch!"
+                );
+                let total = weights.iter().fold(0u64, |acc, w| acc.saturating_add(*w));
+                assert!(*quorum >= 1 && *quorum <= total, "invalid signing quorum!");
+            }
+            None => {}
+        }
+    }
+
+    /// Checks that every signatory belongs to exactly one non-empty signing stage.
+    pub fn validate_signing_order(&self) {
+        if let Some(SigningOrder::Stages(stages)) = &self.signing_order {
+            assert!(
+                stages.iter().all(|stage| !stage.is_empty()),
+                "invalid signing order!"
+            );
+            let staged: Vec<&String> = stages.iter().flatten().collect();
+            assert!(
+                staged.len() == self.signatories.len()
+                    && self
+                        .signatories
+                        .iter()
+                        .all(|signatory| staged.contains(&signatory)),
+                "invalid signing order!"
+            );
+        }
+    }
+
+    /// Signatories grouped by the stage they sign in.
+    pub fn signing_stages(&self) -> Vec<Vec<String>> {
+        match &self.signing_order {
+            Some(SigningOrder::Sequential) => self
+                .signatories
+                .iter()
+                .map(|signatory| vec![signatory.clone()])
+                .collect(),
+            Some(SigningOrder::Stages(stages)) => stages.clone(),
+            None => vec![self.signatories.clone()],
+        }
+    }
+
+    /// Signatories allowed to sign next, given the signatories in `signed` and `rejected`. A rejection
+    /// resolves its slot like a signature, so it doesn't hold the later stages back.
+    pub fn next_expected_signers(&self, signed: &[String], rejected: &[String]) -> Vec<String> {
+        self.signing_stages()
+            .into_iter()
+            .map(|stage| {
+                stage
+                    .into_iter()
+                    .filter(|signatory| {
+                        !signed.contains(signatory) && !rejected.contains(signatory)
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .find(|pending| !pending.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Checks whether the signing deadline passed at `now`.
+    pub fn is_deadline_passed(&self, now: u64) -> bool {
+        match self.sign_deadline {
+            Some(deadline) => now >= deadline,
+            None => false,
+        }
+    }
+
+    /// Status of the attestation given the signatories who signed and rejected it, whether its
+    /// Proof-of-Agreement is formed and the current timestamp.
+    pub fn status(
+        &self,
+        signed: &[String],
+        rejected: &[String],
+        is_completed: bool,
+        now: u64,
+    ) -> AttestationStatus {
+        if self.is_revoked {
+            AttestationStatus::Revoked
+        } else if self.is_cancelled {
+            AttestationStatus::Cancelled
+        } else if is_completed {
+            AttestationStatus::Completed
+        } else if !self.is_threshold_reachable(rejected) {
+            AttestationStatus::Rejected
+        } else if self.is_deadline_passed(now) {
+            AttestationStatus::Expired
+        } else if !signed.is_empty() {
+            AttestationStatus::Pending
+        } else {
+            AttestationStatus::Draft
+        }
+    }
+
+    /// Weight required to form the Proof-of-Agreement.
+    pub fn required_weight(&self) -> u64 {
+        match &self.threshold {
+            Some(SigningThreshold::Count(m)) => *m as u64,
+            Some(SigningThreshold::Weighted { quorum, .. }) => *quorum,
+            None => self.signatories.len() as u64,
+        }
+    }
+
+    /// Weight of the signatories accepted by `is_counted`.
+    pub fn weight_of(&self, is_counted: &dyn Fn(&str) -> bool) -> u64 {
+        self.signatories
+            .iter()
+            .enumerate()
+            .filter(|(_, signatory)| is_counted(signatory))
+            .map(|(i, _)| match &self.threshold {
+                Some(SigningThreshold::Weighted { weights, .. }) => weights[i],
+                _ => 1,
+            })
+            .fold(0u64, |acc, w| acc.saturating_add(w))
+    }
+
+    /// Checks whether the signatories in `signed` reach the signing threshold.
+    pub fn is_threshold_reached(&self, signed: &[String]) -> bool {
+        self.weight_of(&|signatory| signed.iter().any(|s| s == signatory)) >= self.required_weight()
+    }
+
+    /// Checks whether the signing threshold can still be reached without the signatories in `rejected`.
+    pub fn is_threshold_reachable(&self, rejected: &[String]) -> bool {
+        self.weight_of(&|signatory| !rejected.iter().any(|r| r == signatory))
+            >= self.required_weight()
+    }
+
+    /// Signatory policies the `signatory` must satisfy: the policy of its role if it has one,
+    /// otherwise every policy of the schema.
+    pub fn policies_for(&self, s: &Schema, signatory: &str) -> Vec<SignatoryPolicy> {
+        match self
+            .signatory_roles
+            .iter()
+            .find(|r| r.signatory == signatory)
+            .and_then(|r| r.policy.resolve(s))
+        {
+            Some(i) => vec![s.signatory_policy[i].clone()],
+            None => s.signatory_policy.clone(),
+        }
+    }
+
+    /// Checks the attestation against a policy requirement, `s` is the schema of the attestation
+    /// and `now` the current timestamp. Cancelled attestations never satisfy a requirement, and
+    /// the age is counted from `stored_at` as the `created_at` claimed by the creator can't be trusted.
+    pub fn satisfies(&self, req: &AttestationRequirement, s: &Schema, now: u64) -> bool {
+        if self.schema_id != req.schema_id || self.is_cancelled {
+            return false;
+        }
+
+        if req.not_revoked && self.is_revoked {
+            return false;
+        }
+
+        // Attestations expire with the `expire_in` of their schema
+        if req.not_expired
+            && s.metadata.expire_in != 0
+            && self.stored_at.saturating_add(s.metadata.expire_in) <= now
+        {
+            return false;
+        }
+
+        if !req.trusted_creators.is_empty() && !req.trusted_creators.contains(&self.creator) {
+            return false;
+        }
 
-        // Check if the schema has expired based on its metadata
-        if s.metadata.expire_in != 0 {
-            assert!(
-                (s.metadata.created_at + s.metadata.expire_in
-                    < current_timestamp.try_into().unwrap()),
-                "schema already expired!"
-            );
+        if let Some(max_age) = req.max_age {
+            if now.saturating_sub(self.stored_at) > max_age {
+                return false;
+            }
         }
 
-        // Ensure that the length of attestation results matches the length of the schema definition
+        req.fields.iter().all(|field| {
+            self.attestation_result
+                .iter()
+                .any(|r| r.name == field.name && field.matches(&r.value))
+        })
+    }
+
+    /// Checks that the attestation can amend `original`: same schema, creator, signatories and
+    /// signing rules, so the amendment is agreed by the signatories of the original.
+    pub fn validate_amendment(&self, original: &Attestation) {
         assert!(
-            self.attestation_result.len() == s.schema_definition.len(),
-            "attestation length mismatch!"
+            self.supersedes == Some(original.attestation_id),
+            "invalid amendment!"
         );
+        assert!(!original.is_revoked, "attestation revoked!");
+        assert!(!original.is_cancelled, "attestation cancelled!");
+        assert!(
+            self.schema_id == original.schema_id,
+            "amendment schema mismatch!"
+        );
+        assert!(self.creator == original.creator, "unauthorized amendment!");
+        assert!(
+            self.signatories == original.signatories
+                && self.signatory_roles == original.signatory_roles
+                && self.threshold == original.threshold
+                && self.signing_order == original.signing_order,
+            "amendment signatories mismatch!"
+        );
+    }
 
-        let mut i = 0;
-        while i < s.schema_definition.len() {
-            // Check that the names match between the schema definition and the attestation result
-            assert!(
-                s.schema_definition[i].definition_name == self.attestation_result[i].name,
-                "attestation name mismatch!"
-            );
-            // Check that the types match between the schema definition and the attestation result
-            assert!(
-                s.schema_definition[i].definition_type
-                    == self.attestation_result[i].attestation_result_type,
-                "attestation type mismatch!"
-            );
-            i += 1;
-        }
+    pub fn validate_cancel(&self, sig: Vec<u8>) {
+        assert!(!self.is_revoked, "attestation revoked!");
+        assert!(!self.is_cancelled, "attestation already cancelled!");
+        // Without signatories there is no agreement to withdraw, only a revoke applies
+        assert!(
+            !self.signatories.is_empty(),
+            "attestation awaits no signatures!"
+        );
 
-        let signature = Signature::from_bytes(&self.signature).expect("Invalid signature");
+        // Only the creator withdraws the agreement
+        assert!(
+            self.creator == env::signer_account_id(),
+            "unauthorized attestator!"
+        );
+
+        let signature = Signature::from_bytes(&sig).expect("Invalid signature");
 
         let caller_pk = env::signer_account_pk(); // ✅ Extract raw bytes from `near_sdk::PublicKey`
 
@@ -166,7 +995,7 @@ impl Attestation {
             PublicKey::from_bytes(ed25519_bytes).expect("❌ Failed to parse Dalek PublicKey");
         //Check signature
         assert!(
-            recover(caller, signature, &self.to_ed25519_message()),
+            recover(caller, signature, &self.to_ed25519_message_cancel()),
             "invalid signature"
         );
     }
@@ -200,6 +1029,9 @@ impl Attestation {
 mod test {
     use super::*;
     use daosign_ed25519::recover;
+    use daosign_schema::{
+        CompareOp, FieldPredicate, PoaMintPolicy, PolicyOperator, SchemaDefinition, SchemaMetadata,
+    };
     use ed25519_dalek::{Keypair, Signature, Signer};
     use rand::rngs::OsRng;
 
@@ -246,6 +1078,18 @@ mod test {
             is_revoked: false,
             revoked_at: 0,
             revoke_signature: vec![0; 65],
+            signatory_roles: vec![],
+            threshold: None,
+            signing_order: None,
+            sign_deadline: None,
+            is_cancelled: false,
+            cancelled_at: 0,
+            cancel_signature: vec![],
+            documents: vec![],
+            fields_root: vec![],
+            refers_to: None,
+            supersedes: None,
+            stored_at: 0,
         };
         // Serialize the schema to message and sign it
         let message = attestation.to_ed25519_message();
@@ -292,6 +1136,18 @@ mod test {
             is_revoked: false,
             revoked_at: 0,
             revoke_signature: vec![0; 65],
+            signatory_roles: vec![],
+            threshold: None,
+            signing_order: None,
+            sign_deadline: None,
+            is_cancelled: false,
+            cancelled_at: 0,
+            cancel_signature: vec![],
+            documents: vec![],
+            fields_root: vec![],
+            refers_to: None,
+            supersedes: None,
+            stored_at: 0,
         };
         // Serialize the schema to message and sign it
         let message = attestation.to_ed25519_message_revoke();
@@ -303,4 +1159,723 @@ mod test {
         // Assert that the signature is valid
         assert!(success, "The signature should be valid.");
     }
+
+    fn create_schema() -> Schema {
+        Schema {
+            schema_id: 0,
+            metadata: SchemaMetadata {
+                name: String::from("daosign_employment"),
+                description: String::from("Employment agreement"),
+                attestation_type: String::from("agreement"),
+                nft_name: String::from("employment collection"),
+                nft_symbol: String::from("emp"),
+                collection_id: String::from("collection.testnet"),
+                creator: String::from("creator.testnet"),
+                created_at: 1,
+                is_nft: false,
+                is_public: false,
+                is_revokable: true,
+                expire_in: 0,
+                poa_mint_policy: PoaMintPolicy::App,
+            },
+            signatory_policy: vec![
+                SignatoryPolicy {
+                    operator: PolicyOperator::And,
+                    signatory_description: String::from("Employer"),
+                    required_schema_id: vec![0],
+                    expression: None,
+                },
+                SignatoryPolicy {
+                    operator: PolicyOperator::And,
+                    signatory_description: String::from("Employee"),
+                    required_schema_id: vec![1],
+                    expression: None,
+                },
+            ],
+            schema_definition: vec![],
+            signature: vec![0; 65],
+        }
+    }
+
+    fn create_attestation(signatory_roles: Vec<SignatoryRole>) -> Attestation {
+        Attestation {
+            attestation_id: 0,
+            schema_id: 0,
+            attestation_result: vec![],
+            creator: String::from("employer.testnet"),
+            recipient: String::from("employee.testnet"),
+            created_at: 1,
+            signatories: vec![
+                String::from("employer.testnet"),
+                String::from("employee.testnet"),
+            ],
+            signature: vec![0; 65],
+            is_revoked: false,
+            revoked_at: 0,
+            revoke_signature: vec![0; 65],
+            signatory_roles,
+            threshold: None,
+            signing_order: None,
+            sign_deadline: None,
+            is_cancelled: false,
+            cancelled_at: 0,
+            cancel_signature: vec![],
+            documents: vec![],
+            fields_root: vec![],
+            refers_to: None,
+            supersedes: None,
+            stored_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_signatory_roles() {
+        let s = create_schema();
+        let attestation = create_attestation(vec![
+            SignatoryRole {
+                signatory: String::from("employer.testnet"),
+                policy: PolicyRef::Role(String::from("Employer")),
+            },
+            SignatoryRole {
+                signatory: String::from("employee.testnet"),
+                policy: PolicyRef::Index(1),
+            },
+        ]);
+        attestation.validate_signatory_roles(&s);
+
+        let employer = attestation.policies_for(&s, "employer.testnet");
+        assert_eq!(employer, vec![s.signatory_policy[0].clone()]);
+        let employee = attestation.policies_for(&s, "employee.testnet");
+        assert_eq!(employee, vec![s.signatory_policy[1].clone()]);
+
+        // Signatories without a role satisfy every policy
+        let attestation = create_attestation(vec![]);
+        assert_eq!(
+            attestation.policies_for(&s, "employee.testnet"),
+            s.signatory_policy
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown role signatory!")]
+    fn test_unknown_role_signatory() {
+        create_attestation(vec![SignatoryRole {
+            signatory: String::from("stranger.testnet"),
+            policy: PolicyRef::Index(0),
+        }])
+        .validate_signatory_roles(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate signatory role!")]
+    fn test_duplicate_signatory_role() {
+        let role = SignatoryRole {
+            signatory: String::from("employee.testnet"),
+            policy: PolicyRef::Index(1),
+        };
+        create_attestation(vec![role.clone(), role]).validate_signatory_roles(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown signatory policy!")]
+    fn test_unknown_signatory_policy() {
+        create_attestation(vec![SignatoryRole {
+            signatory: String::from("employee.testnet"),
+            policy: PolicyRef::Role(String::from("Contractor")),
+        }])
+        .validate_signatory_roles(&create_schema());
+    }
+
+    #[test]
+    fn test_satisfies_requirement() {
+        let mut s = create_schema();
+        s.metadata.expire_in = 100;
+
+        let mut attestation = create_attestation(vec![]);
+        attestation.stored_at = 50;
+        attestation.attestation_result = vec![AttestationResult {
+            attestation_result_type: String::from("uint8"),
+            name: String::from("age"),
+            value: vec![21],
+        }];
+
+        let req = AttestationRequirement {
+            schema_id: 0,
+            not_revoked: true,
+            not_expired: true,
+            trusted_creators: vec![String::from("employer.testnet")],
+            fields: vec![FieldPredicate {
+                name: String::from("age"),
+                op: CompareOp::Gte,
+                values: vec![vec![18]],
+            }],
+            max_age: Some(60),
+        };
+        assert!(attestation.satisfies(&req, &s, 100));
+
+        // Too old
+        assert!(!attestation.satisfies(&req, &s, 111));
+        // Expired with the schema
+        let fresh = AttestationRequirement {
+            max_age: None,
+            ..req.clone()
+        };
+        assert!(attestation.satisfies(&fresh, &s, 149));
+        assert!(!attestation.satisfies(&fresh, &s, 150));
+        // Untrusted creator
+        let trusted = AttestationRequirement {
+            trusted_creators: vec![String::from("issuer.testnet")],
+            ..req.clone()
+        };
+        assert!(!attestation.satisfies(&trusted, &s, 100));
+        // Field mismatch
+        attestation.attestation_result[0].value = vec![17];
+        assert!(!attestation.satisfies(&req, &s, 100));
+        // Revoked
+        attestation.attestation_result[0].value = vec![21];
+        attestation.is_revoked = true;
+        assert!(!attestation.satisfies(&req, &s, 100));
+        assert!(attestation.satisfies(&AttestationRequirement::exists(0), &s, 100));
+        // The claimed creation time doesn't matter
+        attestation.is_revoked = false;
+        attestation.created_at = 100;
+        assert!(!attestation.satisfies(&fresh, &s, 150));
+        // Cancelled
+        attestation.is_cancelled = true;
+        assert!(!attestation.satisfies(&AttestationRequirement::exists(0), &s, 100));
+    }
+
+    #[test]
+    fn test_signing_threshold() {
+        let employer = String::from("employer.testnet");
+        let employee = String::from("employee.testnet");
+
+        // Every signatory by default
+        let mut attestation = create_attestation(vec![]);
+        attestation.validate_threshold();
+        assert!(!attestation.is_threshold_reached(&[employer.clone()]));
+        assert!(attestation.is_threshold_reached(&[employer.clone(), employee.clone()]));
+
+        attestation.threshold = Some(SigningThreshold::Count(1));
+        attestation.validate_threshold();
+        assert!(!attestation.is_threshold_reached(&[]));
+        assert!(attestation.is_threshold_reached(&[employee.clone()]));
+
+        attestation.threshold = Some(SigningThreshold::Weighted {
+            weights: vec![3, 1],
+            quorum: 3,
+        });
+        attestation.validate_threshold();
+        assert!(!attestation.is_threshold_reached(&[employee.clone()]));
+        assert!(attestation.is_threshold_reached(&[employer.clone()]));
+        // Accounts outside of the signatories don't count
+        assert!(!attestation.is_threshold_reached(&[String::from("stranger.testnet")]));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid signing threshold!")]
+    fn test_invalid_signing_threshold() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.threshold = Some(SigningThreshold::Count(3));
+        attestation.validate_threshold();
+    }
+
+    #[test]
+    #[should_panic(expected = "signatory weights mismatch!")]
+    fn test_signatory_weights_mismatch() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.threshold = Some(SigningThreshold::Weighted {
+            weights: vec![1],
+            quorum: 1,
+        });
+        attestation.validate_threshold();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid signing quorum!")]
+    fn test_invalid_signing_quorum() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.threshold = Some(SigningThreshold::Weighted {
+            weights: vec![1, 1],
+            quorum: 3,
+        });
+        attestation.validate_threshold();
+    }
+
+    #[test]
+    fn test_signing_order() {
+        let employer = String::from("employer.testnet");
+        let employee = String::from("employee.testnet");
+
+        // Any order by default
+        let mut attestation = create_attestation(vec![]);
+        assert_eq!(
+            attestation.next_expected_signers(&[], &[]),
+            vec![employer.clone(), employee.clone()]
+        );
+
+        attestation.signing_order = Some(SigningOrder::Sequential);
+        attestation.validate_signing_order();
+        assert_eq!(
+            attestation.next_expected_signers(&[], &[]),
+            vec![employer.clone()]
+        );
+        assert_eq!(
+            attestation.next_expected_signers(&[employer.clone()], &[]),
+            vec![employee.clone()]
+        );
+
+        attestation.signing_order = Some(SigningOrder::Stages(vec![
+            vec![employee.clone()],
+            vec![employer.clone()],
+        ]));
+        attestation.validate_signing_order();
+        assert_eq!(
+            attestation.next_expected_signers(&[], &[]),
+            vec![employee.clone()]
+        );
+        assert!(attestation
+            .next_expected_signers(&[employee.clone(), employer.clone()], &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_signing_order_with_rejection() {
+        let employer = String::from("employer.testnet");
+        let employee = String::from("employee.testnet");
+
+        // 1 of 2 signatories, one after the other
+        let mut attestation = create_attestation(vec![]);
+        attestation.signing_order = Some(SigningOrder::Sequential);
+        attestation.threshold = Some(SigningThreshold::Count(1));
+
+        // The rejection of the first signatory lets the next one sign
+        let rejected = vec![employer.clone()];
+        assert_eq!(
+            attestation.next_expected_signers(&[], &rejected),
+            vec![employee.clone()]
+        );
+        assert_eq!(
+            attestation.status(&[], &rejected, false, 0),
+            AttestationStatus::Draft
+        );
+
+        let rejected = vec![employer.clone(), employee.clone()];
+        assert!(attestation.next_expected_signers(&[], &rejected).is_empty());
+        assert_eq!(
+            attestation.status(&[], &rejected, false, 0),
+            AttestationStatus::Rejected
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid signing order!")]
+    fn test_invalid_signing_order() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.signing_order = Some(SigningOrder::Stages(vec![vec![String::from(
+            "employer.testnet",
+        )]]));
+        attestation.validate_signing_order();
+    }
+
+    #[test]
+    fn test_attestation_status() {
+        let employer = String::from("employer.testnet");
+        let employee = String::from("employee.testnet");
+
+        let mut attestation = create_attestation(vec![]);
+        assert_eq!(
+            attestation.status(&[], &[], false, 0),
+            AttestationStatus::Draft
+        );
+        assert_eq!(
+            attestation.status(&[employer.clone()], &[], false, 0),
+            AttestationStatus::Pending
+        );
+        assert_eq!(
+            attestation.status(&[employer.clone(), employee.clone()], &[], true, 0),
+            AttestationStatus::Completed
+        );
+
+        attestation.sign_deadline = Some(100);
+        assert_eq!(
+            attestation.status(&[employer.clone()], &[], false, 99),
+            AttestationStatus::Pending
+        );
+        assert_eq!(
+            attestation.status(&[employer.clone()], &[], false, 100),
+            AttestationStatus::Expired
+        );
+        // A formed agreement doesn't expire
+        assert_eq!(
+            attestation.status(&[employer.clone(), employee.clone()], &[], true, 100),
+            AttestationStatus::Completed
+        );
+
+        // Every signatory must sign, a single rejection is final
+        assert_eq!(
+            attestation.status(&[employer.clone()], &[employee.clone()], false, 0),
+            AttestationStatus::Rejected
+        );
+
+        attestation.is_cancelled = true;
+        assert_eq!(
+            attestation.status(&[employer.clone()], &[], false, 0),
+            AttestationStatus::Cancelled
+        );
+
+        attestation.is_revoked = true;
+        assert_eq!(
+            attestation.status(&[], &[], true, 0),
+            AttestationStatus::Revoked
+        );
+    }
+
+    #[test]
+    fn test_threshold_reachable() {
+        let employer = String::from("employer.testnet");
+        let employee = String::from("employee.testnet");
+
+        let mut attestation = create_attestation(vec![]);
+        assert!(attestation.is_threshold_reachable(&[]));
+        assert!(!attestation.is_threshold_reachable(&[employee.clone()]));
+
+        attestation.threshold = Some(SigningThreshold::Count(1));
+        assert!(attestation.is_threshold_reachable(&[employee.clone()]));
+        assert!(!attestation.is_threshold_reachable(&[employee.clone(), employer.clone()]));
+
+        attestation.threshold = Some(SigningThreshold::Weighted {
+            weights: vec![3, 1],
+            quorum: 3,
+        });
+        assert!(attestation.is_threshold_reachable(&[employee.clone()]));
+        assert!(!attestation.is_threshold_reachable(&[employer.clone()]));
+    }
+
+    fn create_ordered_attestation(signatories: Vec<&str>) -> Attestation {
+        let mut attestation = create_attestation(vec![]);
+        attestation.signatories = signatories.into_iter().map(String::from).collect();
+        attestation
+    }
+
+    #[test]
+    fn test_validate_signatories() {
+        let schema = create_schema();
+
+        create_ordered_attestation(vec!["employee.testnet", "employer.testnet"])
+            .validate_signatories(&schema);
+
+        // Sequential signatories keep their signing order
+        let mut attestation =
+            create_ordered_attestation(vec!["employer.testnet", "employee.testnet"]);
+        attestation.signing_order = Some(SigningOrder::Sequential);
+        attestation.validate_signatories(&schema);
+
+        // No recipient
+        let mut attestation = create_ordered_attestation(vec!["employer.testnet"]);
+        attestation.recipient = String::new();
+        attestation.validate_signatories(&schema);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid creator account!")]
+    fn test_invalid_creator_account() {
+        let mut attestation = create_ordered_attestation(vec!["employer.testnet"]);
+        attestation.creator = String::from("Employer");
+        attestation.validate_signatories(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid recipient account!")]
+    fn test_invalid_recipient_account() {
+        let mut attestation = create_ordered_attestation(vec!["employer.testnet"]);
+        attestation.recipient = String::from("employee..testnet");
+        attestation.validate_signatories(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid signatory account!")]
+    fn test_invalid_signatory_account() {
+        create_ordered_attestation(vec!["employee.testnet", "not an account"])
+            .validate_signatories(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "no signatories!")]
+    fn test_no_signatories() {
+        create_ordered_attestation(vec![]).validate_signatories(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "too many signatories!")]
+    fn test_too_many_signatories() {
+        let signatories: Vec<String> = (0..=MAX_SIGNATORIES)
+            .map(|i| format!("signatory{:02}.testnet", i))
+            .collect();
+        create_ordered_attestation(signatories.iter().map(|s| s.as_str()).collect())
+            .validate_signatories(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate signatory!")]
+    fn test_duplicate_signatory() {
+        create_ordered_attestation(vec!["employee.testnet", "employee.testnet"])
+            .validate_signatories(&create_schema());
+    }
+
+    #[test]
+    #[should_panic(expected = "signatories not in canonical order!")]
+    fn test_signatories_not_in_canonical_order() {
+        create_ordered_attestation(vec!["employer.testnet", "employee.testnet"])
+            .validate_signatories(&create_schema());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let attestation = create_attestation(vec![]);
+        assert_eq!(attestation.content_hash().len(), 32);
+
+        // Any change of the content changes the hash
+        let mut changed = attestation.clone();
+        changed.attestation_result = vec![AttestationResult {
+            attestation_result_type: String::from("uint256"),
+            name: String::from("salary"),
+            value: vec![1],
+        }];
+        assert_ne!(attestation.content_hash(), changed.content_hash());
+
+        // Signatures are not part of the content
+        let mut signed = attestation.clone();
+        signed.signature = vec![1; 64];
+        assert_eq!(attestation.content_hash(), signed.content_hash());
+    }
+
+    #[test]
+    fn test_bulk_attestation_includes() {
+        let attestations: Vec<Attestation> = (0..3)
+            .map(|i| {
+                let mut a = create_attestation(vec![]);
+                a.attestation_id = i;
+                a
+            })
+            .collect();
+        let leaves: Vec<Vec<u8>> = attestations.iter().map(|a| a.leaf_hash()).collect();
+        let bulk = BulkAttestation {
+            bulk_id: 0,
+            schema_id: 0,
+            creator: String::from("employer.testnet"),
+            merkle_root: merkle::root(&leaves),
+            leaves: 3,
+            created_at: 1,
+            signature: vec![0; 64],
+        };
+
+        for (i, a) in attestations.iter().enumerate() {
+            assert!(bulk.includes(a, &merkle::proof(&leaves, i)));
+        }
+
+        // Altered content is not under the root
+        let mut altered = attestations[1].clone();
+        altered.recipient = String::from("someone.testnet");
+        assert!(!bulk.includes(&altered, &merkle::proof(&leaves, 1)));
+
+        // Nor are attestations of another schema
+        let mut other_schema = attestations[1].clone();
+        other_schema.schema_id = 1;
+        assert!(!bulk.includes(&other_schema, &merkle::proof(&leaves, 1)));
+    }
+
+    fn create_document(hash: Vec<u8>, hash_algorithm: HashAlgorithm) -> DocumentRef {
+        DocumentRef {
+            name: String::from("agreement.pdf"),
+            hash,
+            hash_algorithm,
+            location: Some(String::from(
+                "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            )),
+        }
+    }
+
+    #[test]
+    fn test_documents() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.documents = vec![
+            create_document(vec![1; 32], HashAlgorithm::Sha256),
+            create_document(vec![2; 64], HashAlgorithm::Sha512),
+        ];
+        attestation.validate_documents();
+
+        // Documents are part of the signed content
+        let mut other = attestation.clone();
+        other.documents[0].location = None;
+        assert_ne!(attestation.content_hash(), other.content_hash());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid document hash length!")]
+    fn test_invalid_document_hash_length() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.documents = vec![create_document(vec![1; 32], HashAlgorithm::Sha512)];
+        attestation.validate_documents();
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate document!")]
+    fn test_duplicate_document() {
+        let mut attestation = create_attestation(vec![]);
+        attestation.documents = vec![
+            create_document(vec![1; 32], HashAlgorithm::Sha256),
+            create_document(vec![1; 32], HashAlgorithm::Keccak256),
+        ];
+        attestation.validate_documents();
+    }
+
+    fn create_private_schema() -> Schema {
+        let mut s = create_schema();
+        s.schema_definition = vec![
+            SchemaDefinition {
+                definition_type: String::from("string"),
+                definition_name: String::from("position"),
+                privacy: FieldPrivacy::Plaintext,
+            },
+            SchemaDefinition {
+                definition_type: String::from("uint256"),
+                definition_name: String::from("salary"),
+                privacy: FieldPrivacy::HashCommitment,
+            },
+            SchemaDefinition {
+                definition_type: String::from("string"),
+                definition_name: String::from("address"),
+                privacy: FieldPrivacy::Encrypted,
+            },
+        ];
+        s
+    }
+
+    fn create_encrypted_value(readers: &[&str]) -> EncryptedValue {
+        EncryptedValue {
+            algorithm: String::from("x25519-xsalsa20-poly1305"),
+            nonce: vec![1; 24],
+            ciphertext: vec![2; 48],
+            keys: readers
+                .iter()
+                .map(|reader| WrappedKey {
+                    account_id: reader.to_string(),
+                    public_key: vec![3; 32],
+                    ephemeral_public_key: vec![4; 32],
+                    wrapped_key: vec![5; 48],
+                })
+                .collect(),
+        }
+    }
+
+    fn create_private_attestation(salary: Vec<u8>, address: Vec<u8>) -> Attestation {
+        let mut attestation = create_attestation(vec![]);
+        attestation.attestation_result = vec![
+            AttestationResult {
+                attestation_result_type: String::from("string"),
+                name: String::from("position"),
+                value: b"engineer".to_vec(),
+            },
+            AttestationResult {
+                attestation_result_type: String::from("uint256"),
+                name: String::from("salary"),
+                value: salary,
+            },
+            AttestationResult {
+                attestation_result_type: String::from("string"),
+                name: String::from("address"),
+                value: address,
+            },
+        ];
+        attestation
+    }
+
+    #[test]
+    fn test_private_fields() {
+        let s = create_private_schema();
+        let salt = [9; 32];
+        let encrypted = create_encrypted_value(&["employer.testnet", "employee.testnet"]);
+        let attestation =
+            create_private_attestation(field_commitment(&salt, &[16, 0]), encrypted.encode());
+        for (i, definition) in s.schema_definition.iter().enumerate() {
+            attestation.validate_private_field(
+                definition.privacy,
+                &attestation.attestation_result[i].value,
+            );
+        }
+
+        // The committed value is revealed with its salt
+        assert!(attestation.verify_field_reveal(&s, "salary", &salt, &[16, 0]));
+        assert!(!attestation.verify_field_reveal(&s, "salary", &salt, &[16, 1]));
+        assert!(!attestation.verify_field_reveal(&s, "salary", &salt[..8], &[16, 0]));
+        assert!(!attestation.verify_field_reveal(&s, "position", &salt, b"engineer"));
+        assert!(!attestation.verify_field_reveal(&s, "bonus", &salt, &[1]));
+
+        // Events only carry the hash of encrypted values
+        let redacted = attestation.redacted(&s);
+        assert_eq!(
+            redacted.attestation_result[2].value,
+            Sha256::digest(encrypted.encode()).to_vec()
+        );
+        assert_eq!(
+            redacted.attestation_result[..2],
+            attestation.attestation_result[..2]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid field commitment!")]
+    fn test_invalid_field_commitment() {
+        let attestation = create_private_attestation(vec![16, 0], vec![]);
+        attestation.validate_private_field(FieldPrivacy::HashCommitment, &[16, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing field key!")]
+    fn test_missing_field_key() {
+        let attestation = create_private_attestation(vec![0; 32], vec![]);
+        let encrypted = create_encrypted_value(&["employer.testnet"]);
+        attestation.validate_private_field(FieldPrivacy::Encrypted, &encrypted.encode());
+    }
+
+    #[test]
+    fn test_validate_amendment() {
+        let original = create_attestation(vec![]);
+        let mut amendment = create_attestation(vec![]);
+        amendment.attestation_id = 1;
+        amendment.supersedes = Some(0);
+        amendment.attestation_result = vec![AttestationResult {
+            attestation_result_type: String::from("uint256"),
+            name: String::from("salary"),
+            value: vec![32, 0],
+        }];
+        amendment.validate_amendment(&original);
+    }
+
+    #[test]
+    #[should_panic(expected = "amendment signatories mismatch!")]
+    fn test_amendment_signatories_mismatch() {
+        let original = create_attestation(vec![]);
+        let mut amendment = create_attestation(vec![]);
+        amendment.supersedes = Some(0);
+        amendment.signatories = vec![String::from("employer.testnet")];
+        amendment.validate_amendment(&original);
+    }
+
+    #[test]
+    #[should_panic(expected = "amendment signatories mismatch!")]
+    fn test_amendment_signatory_roles_mismatch() {
+        let original = create_attestation(vec![SignatoryRole {
+            signatory: String::from("employer.testnet"),
+            policy: PolicyRef::Role(String::from("Employer")),
+        }]);
+        // The same signatory bound to another policy
+        let mut amendment = create_attestation(vec![SignatoryRole {
+            signatory: String::from("employer.testnet"),
+            policy: PolicyRef::Index(0),
+        }]);
+        amendment.supersedes = Some(0);
+        amendment.validate_amendment(&original);
+    }
 }
@@ -0,0 +1,116 @@
+//! Binary sha256 Merkle tree with sorted pairs.
+//!
+//! Leaves and inner nodes are hashed with different prefixes so a node can never be passed off
+//! as a leaf. Pairs are sorted before hashing, so a proof is just the list of sibling hashes
+//! from the leaf up to the root. A node without a sibling is promoted to the next level as is.
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Length of every hash in the tree.
+pub const HASH_LEN: usize = 32;
+
+/// Hashes raw leaf content.
+pub fn leaf_hash(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.finalize().to_vec()
+}
+
+/// Hashes two child nodes into their parent.
+pub fn node_hash(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+fn next_level(level: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => node_hash(a, b),
+            [a] => a.clone(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Computes the root of the given leaf hashes. Returns an empty vector if there are no leaves.
+pub fn root(leaves: &[Vec<u8>]) -> Vec<u8> {
+    if leaves.is_empty() {
+        return Vec::new();
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.remove(0)
+}
+
+/// Builds the inclusion proof of the leaf at `index`.
+pub fn proof(leaves: &[Vec<u8>], index: usize) -> Vec<Vec<u8>> {
+    assert!(index < leaves.len(), "leaf index out of range!");
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut i = index;
+    while level.len() > 1 {
+        let sibling = i ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling].clone());
+        }
+        level = next_level(&level);
+        i /= 2;
+    }
+    proof
+}
+
+/// Checks that `leaf` is included under `root` using the sibling hashes in `proof`.
+pub fn verify(root: &[u8], leaf: &[u8], proof: &[Vec<u8>]) -> bool {
+    if root.len() != HASH_LEN || proof.iter().any(|p| p.len() != HASH_LEN) {
+        return false;
+    }
+    let computed = proof
+        .iter()
+        .fold(leaf.to_vec(), |acc, sibling| node_hash(&acc, sibling));
+    computed == root
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|i| leaf_hash(&[i as u8])).collect()
+    }
+
+    #[test]
+    fn test_merkle_proofs() {
+        for n in 1..=9 {
+            let leaves = leaves(n);
+            let root = root(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                assert!(verify(&root, leaf, &proof(&leaves, i)));
+            }
+        }
+        assert_eq!(root(&leaves(1)), leaf_hash(&[0]));
+        assert!(root(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_merkle_invalid_proof() {
+        let leaves = leaves(5);
+        let root = root(&leaves);
+
+        // leaf not in the tree
+        assert!(!verify(&root, &leaf_hash(&[9]), &proof(&leaves, 0)));
+        // proof of another leaf
+        assert!(!verify(&root, &leaves[0], &proof(&leaves, 3)));
+        // malformed root
+        assert!(!verify(&root[1..], &leaves[0], &proof(&leaves, 0)));
+    }
+}
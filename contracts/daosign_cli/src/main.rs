@@ -0,0 +1,520 @@
+//! `daosign` prepares, signs and verifies DAOsign records from local files, without a node.
+
+mod template;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use daosign_client::{
+    args, decode_public_key, Attestation, DaoSignSigner, ProofOfSignature, ProofOfSignatureBuilder,
+    Schema,
+};
+use daosign_proof_of_agreement::ProofOfAgreement;
+use serde::{de::DeserializeOwned, Serialize};
+
+use template::{AttestationValues, SchemaTemplate};
+
+#[derive(Parser)]
+#[command(
+    name = "daosign",
+    version,
+    about = "Prepare, sign and verify DAOsign records offline"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Schema templates.
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommand,
+    },
+    /// Attestations of a schema.
+    Attestation {
+        #[command(subcommand)]
+        command: AttestationCommand,
+    },
+    /// Sign a record with a near-cli credentials file.
+    Sign {
+        kind: SignKind,
+        /// Schema or attestation to sign. For `pos`, the attestation as stored by the contract.
+        input: PathBuf,
+        /// near-cli credentials file, e.g. ~/.near-credentials/testnet/<account>.json
+        #[arg(long)]
+        key: PathBuf,
+        /// Signing time of a `pos` in nanoseconds, now by default.
+        #[arg(long)]
+        signed_at: Option<u64>,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Verify a signed record.
+    Verify {
+        #[command(subcommand)]
+        command: VerifyCommand,
+    },
+    /// Print the arguments of the contract method storing the record.
+    Export { kind: ExportKind, input: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum SchemaCommand {
+    /// Create an unsigned schema from a YAML or JSON template.
+    New {
+        template: PathBuf,
+        #[arg(long)]
+        creator: String,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AttestationCommand {
+    /// Create an unsigned attestation from a schema and a YAML or JSON file of values.
+    Fill {
+        #[arg(long)]
+        schema: PathBuf,
+        values: PathBuf,
+        #[arg(long)]
+        creator: String,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VerifyCommand {
+    Schema {
+        input: PathBuf,
+        /// ed25519:<base58> key of the creator
+        #[arg(long)]
+        public_key: String,
+    },
+    Attestation {
+        input: PathBuf,
+        /// ed25519:<base58> key of the creator
+        #[arg(long)]
+        public_key: String,
+    },
+    Pos {
+        input: PathBuf,
+        #[arg(long)]
+        attestation: PathBuf,
+        /// ed25519:<base58> key of the signer
+        #[arg(long)]
+        public_key: String,
+    },
+    Poa {
+        input: PathBuf,
+        #[arg(long)]
+        attestation: PathBuf,
+        /// YAML or JSON map of signer account => ed25519:<base58> key
+        #[arg(long)]
+        keys: PathBuf,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+enum SignKind {
+    Schema,
+    Attestation,
+    Pos,
+}
+
+#[derive(Clone, ValueEnum)]
+enum ExportKind {
+    Schema,
+    Attestation,
+    Pos,
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    match cli.command {
+        Command::Schema {
+            command:
+                SchemaCommand::New {
+                    template,
+                    creator,
+                    output,
+                },
+        } => {
+            let template: SchemaTemplate = read(&template)?;
+            write(output, &template.to_schema(&creator, now()))
+        }
+        Command::Attestation {
+            command:
+                AttestationCommand::Fill {
+                    schema,
+                    values,
+                    creator,
+                    output,
+                },
+        } => {
+            let schema: Schema = read(&schema)?;
+            let values: AttestationValues = read(&values)?;
+            write(output, &values.to_attestation(&schema, &creator, now())?)
+        }
+        Command::Sign {
+            kind,
+            input,
+            key,
+            signed_at,
+            output,
+        } => {
+            let signer = DaoSignSigner::from_credentials_file(&key).map_err(|e| e.to_string())?;
+            match kind {
+                SignKind::Schema => {
+                    let mut schema: Schema = read(&input)?;
+                    signer.sign_schema(&mut schema);
+                    write(output, &schema)
+                }
+                SignKind::Attestation => {
+                    let mut attestation: Attestation = read(&input)?;
+                    signer.sign_attestation(&mut attestation);
+                    write(output, &attestation)
+                }
+                SignKind::Pos => {
+                    let attestation: Attestation = read(&input)?;
+                    let pos = ProofOfSignatureBuilder::new(&attestation, signer.account_id())
+                        .signed_at(signed_at.unwrap_or_else(now))
+                        .sign(&signer);
+                    write(output, &pos)
+                }
+            }
+        }
+        Command::Verify { command } => {
+            let verified = match command {
+                VerifyCommand::Schema { input, public_key } => {
+                    let schema: Schema = read(&input)?;
+                    daosign_verify::verify_schema(&schema, &public_key_bytes(&public_key)?)
+                }
+                VerifyCommand::Attestation { input, public_key } => {
+                    let attestation: Attestation = read(&input)?;
+                    daosign_verify::verify_attestation(
+                        &attestation,
+                        &public_key_bytes(&public_key)?,
+                    )
+                }
+                VerifyCommand::Pos {
+                    input,
+                    attestation,
+                    public_key,
+                } => {
+                    let pos: ProofOfSignature = read(&input)?;
+                    let attestation: Attestation = read(&attestation)?;
+                    daosign_verify::verify_pos(&pos, &attestation, &public_key_bytes(&public_key)?)
+                }
+                VerifyCommand::Poa {
+                    input,
+                    attestation,
+                    keys,
+                } => {
+                    let poa: ProofOfAgreement = read(&input)?;
+                    let attestation: Attestation = read(&attestation)?;
+                    let keys: BTreeMap<String, String> = read(&keys)?;
+                    let mut public_keys = BTreeMap::new();
+                    for (account_id, key) in keys {
+                        public_keys.insert(account_id, public_key_bytes(&key)?);
+                    }
+                    daosign_verify::verify_poa(&poa, &attestation, &public_keys)
+                }
+            };
+            verified.map_err(|err| format!("verification failed: {:?}", err))?;
+            println!("OK");
+            Ok(())
+        }
+        Command::Export { kind, input } => {
+            let args = match kind {
+                ExportKind::Schema => args::store_schema(&read(&input)?),
+                ExportKind::Attestation => args::store_attestation(&read(&input)?),
+                ExportKind::Pos => args::store_pos(&read(&input)?),
+            };
+            println!("{}", args);
+            Ok(())
+        }
+    }
+}
+
+/// Reads a YAML or JSON file, JSON being a subset of YAML.
+fn read<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    serde_yaml::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Writes the record as JSON to `output`, or stdout.
+fn write<T: Serialize>(output: Option<PathBuf>, record: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(record).map_err(|err| err.to_string())?;
+    match output {
+        Some(path) => fs::write(&path, json).map_err(|err| format!("{}: {}", path.display(), err)),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+fn public_key_bytes(public_key: &str) -> Result<Vec<u8>, String> {
+    decode_public_key(public_key).map_err(|err| err.to_string())
+}
+
+/// Current time in nanoseconds, the unit of block timestamps.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use daosign_proof_of_agreement::AgreementSignature;
+
+    const ALICE_SEED: &str = "ed25519:4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi";
+    const BOB_SEED: &str = "ed25519:8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR";
+
+    const SCHEMA: &str = r#"
+name: employment
+description: Employment agreement
+fields:
+  - name: position
+    type: string
+  - name: salary
+    type: uint256
+"#;
+
+    const VALUES: &str = r#"
+recipient: bob.testnet
+signatories: [bob.testnet, alice.testnet]
+values:
+  position: engineer
+  salary: 4096
+"#;
+
+    fn daosign(args: &[&str]) -> Result<(), String> {
+        let cli = Cli::try_parse_from(std::iter::once("daosign").chain(args.iter().copied()))
+            .map_err(|err| err.to_string())?;
+        run(cli)
+    }
+
+    /// Empty scratch directory of a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("daosign_cli").join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes the near-cli credentials file of `account_id`, returns its path and public key.
+    fn credentials(dir: &Path, account_id: &str, seed: &str) -> (String, String) {
+        let path = dir.join(format!("{}.json", account_id));
+        fs::write(
+            &path,
+            format!(
+                r#"{{"account_id":"{}","private_key":"{}"}}"#,
+                account_id, seed
+            ),
+        )
+        .unwrap();
+        let signer = DaoSignSigner::from_secret_key(account_id, seed).unwrap();
+        (path_str(&path), signer.public_key_str())
+    }
+
+    fn path_str(path: &Path) -> String {
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let dir = test_dir("sign_and_verify");
+        let file = |name: &str| path_str(&dir.join(name));
+        let (alice_key, alice_pk) = credentials(&dir, "alice.testnet", ALICE_SEED);
+        let (bob_key, bob_pk) = credentials(&dir, "bob.testnet", BOB_SEED);
+        fs::write(dir.join("template.yaml"), SCHEMA).unwrap();
+        fs::write(dir.join("values.yaml"), VALUES).unwrap();
+
+        // Schema
+        daosign(&[
+            "schema",
+            "new",
+            &file("template.yaml"),
+            "--creator",
+            "alice.testnet",
+            "-o",
+            &file("schema.json"),
+        ])
+        .unwrap();
+        daosign(&[
+            "sign",
+            "schema",
+            &file("schema.json"),
+            "--key",
+            &alice_key,
+            "-o",
+            &file("schema.json"),
+        ])
+        .unwrap();
+        daosign(&[
+            "verify",
+            "schema",
+            &file("schema.json"),
+            "--public-key",
+            &alice_pk,
+        ])
+        .unwrap();
+        assert!(daosign(&[
+            "verify",
+            "schema",
+            &file("schema.json"),
+            "--public-key",
+            &bob_pk,
+        ])
+        .unwrap_err()
+        .starts_with("verification failed"));
+
+        // Attestation
+        daosign(&[
+            "attestation",
+            "fill",
+            "--schema",
+            &file("schema.json"),
+            &file("values.yaml"),
+            "--creator",
+            "alice.testnet",
+            "-o",
+            &file("attestation.json"),
+        ])
+        .unwrap();
+        daosign(&[
+            "sign",
+            "attestation",
+            &file("attestation.json"),
+            "--key",
+            &alice_key,
+            "-o",
+            &file("attestation.json"),
+        ])
+        .unwrap();
+        daosign(&[
+            "verify",
+            "attestation",
+            &file("attestation.json"),
+            "--public-key",
+            &alice_pk,
+        ])
+        .unwrap();
+        daosign(&["export", "attestation", &file("attestation.json")]).unwrap();
+
+        // Proofs of Signature of both signatories
+        for (name, key) in [("alice", &alice_key), ("bob", &bob_key)] {
+            daosign(&[
+                "sign",
+                "pos",
+                &file("attestation.json"),
+                "--key",
+                key,
+                "--signed-at",
+                "10",
+                "-o",
+                &file(&format!("{}_pos.json", name)),
+            ])
+            .unwrap();
+        }
+        daosign(&[
+            "verify",
+            "pos",
+            &file("bob_pos.json"),
+            "--attestation",
+            &file("attestation.json"),
+            "--public-key",
+            &bob_pk,
+        ])
+        .unwrap();
+        assert!(daosign(&[
+            "verify",
+            "pos",
+            &file("bob_pos.json"),
+            "--attestation",
+            &file("attestation.json"),
+            "--public-key",
+            &alice_pk,
+        ])
+        .is_err());
+
+        // Proof of Agreement formed from them
+        let attestation: Attestation = read(&dir.join("attestation.json")).unwrap();
+        let signatures = ["alice_pos.json", "bob_pos.json"]
+            .iter()
+            .map(|name| {
+                let pos: ProofOfSignature = read(&dir.join(name)).unwrap();
+                AgreementSignature {
+                    account_id: pos.creator,
+                    public_key: pos.public_key,
+                    signature: pos.signature,
+                    signed_at: pos.signed_at,
+                }
+            })
+            .collect();
+        let poa = ProofOfAgreement {
+            attestation_id: attestation.attestation_id,
+            content_hash: attestation.content_hash(),
+            completed_at: 20,
+            signatures,
+        };
+        write(Some(dir.join("poa.json")), &poa).unwrap();
+        fs::write(
+            dir.join("keys.yaml"),
+            format!("alice.testnet: {}\nbob.testnet: {}\n", alice_pk, bob_pk),
+        )
+        .unwrap();
+        daosign(&[
+            "verify",
+            "poa",
+            &file("poa.json"),
+            "--attestation",
+            &file("attestation.json"),
+            "--keys",
+            &file("keys.yaml"),
+        ])
+        .unwrap();
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let dir = test_dir("invalid_input");
+        let file = |name: &str| path_str(&dir.join(name));
+        let (alice_key, _) = credentials(&dir, "alice.testnet", ALICE_SEED);
+
+        let err =
+            daosign(&["sign", "schema", &file("missing.json"), "--key", &alice_key]).unwrap_err();
+        assert!(err.starts_with(&file("missing.json")));
+
+        fs::write(dir.join("schema.json"), "{}").unwrap();
+        assert!(daosign(&["export", "schema", &file("schema.json")]).is_err());
+        assert!(daosign(&[
+            "verify",
+            "schema",
+            &file("schema.json"),
+            "--public-key",
+            "ed25519:abc",
+        ])
+        .is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! Templates the records are prepared from, in YAML or JSON.
+
+use std::collections::BTreeMap;
+
+use daosign_client::{
+    Attestation, AttestationBuilder, FieldPrivacy, PoaMintPolicy, Schema, SchemaBuilder,
+    SignatoryPolicy, SigningOrder, MIN_SALT_LEN,
+};
+use serde::Deserialize;
+
+/// Template of `schema new`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub attestation_type: String,
+    #[serde(default)]
+    pub is_public: bool,
+    #[serde(default)]
+    pub is_revokable: bool,
+    #[serde(default)]
+    pub expire_in: u64,
+    #[serde(default)]
+    pub nft: Option<NftTemplate>,
+    pub fields: Vec<FieldTemplate>,
+    #[serde(default)]
+    pub signatory_policies: Vec<SignatoryPolicy>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NftTemplate {
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub mint_policy: PoaMintPolicy,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldTemplate {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(default)]
+    pub privacy: FieldPrivacy,
+}
+
+impl SchemaTemplate {
+    pub fn to_schema(&self, creator: &str, created_at: u64) -> Schema {
+        let mut builder = SchemaBuilder::new(&self.name, creator)
+            .description(&self.description)
+            .attestation_type(&self.attestation_type)
+            .public(self.is_public)
+            .revokable(self.is_revokable)
+            .expire_in(self.expire_in)
+            .created_at(created_at);
+        if let Some(nft) = &self.nft {
+            builder = builder.nft(&nft.name, &nft.symbol, nft.mint_policy.clone());
+        }
+        for field in &self.fields {
+            builder = builder.private_field(&field.field_type, &field.name, field.privacy);
+        }
+        for policy in &self.signatory_policies {
+            builder = builder.signatory_policy(policy.clone());
+        }
+        builder.build()
+    }
+}
+
+/// Values of `attestation fill`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttestationValues {
+    #[serde(default)]
+    pub recipient: String,
+    pub signatories: Vec<String>,
+    pub values: BTreeMap<String, serde_yaml::Value>,
+    #[serde(default)]
+    pub salts: BTreeMap<String, String>, // 0x hex salts of the `hash_commitment` fields
+    #[serde(default)]
+    pub threshold: Option<u32>,
+    #[serde(default)]
+    pub sequential: bool,
+    #[serde(default)]
+    pub sign_deadline: Option<u64>,
+}
+
+impl AttestationValues {
+    /// Fills the schema fields in definition order.
+    pub fn to_attestation(
+        &self,
+        s: &Schema,
+        creator: &str,
+        created_at: u64,
+    ) -> Result<Attestation, String> {
+        if let Some(name) = self.values.keys().find(|name| {
+            !s.schema_definition
+                .iter()
+                .any(|d| &d.definition_name == *name)
+        }) {
+            return Err(format!("unknown field `{}`", name));
+        }
+
+        let mut builder = AttestationBuilder::new(s.schema_id, creator)
+            .recipient(&self.recipient)
+            .created_at(created_at);
+        for definition in &s.schema_definition {
+            let value = self
+                .values
+                .get(&definition.definition_name)
+                .ok_or_else(|| format!("missing field `{}`", definition.definition_name))?;
+            let encoded = encode_value(&definition.definition_type, value)
+                .map_err(|err| format!("field `{}`: {}", definition.definition_name, err))?;
+            builder = match definition.privacy {
+                FieldPrivacy::Plaintext => builder.result(
+                    &definition.definition_type,
+                    &definition.definition_name,
+                    encoded,
+                ),
+                FieldPrivacy::HashCommitment => {
+                    let salt = self
+                        .salts
+                        .get(&definition.definition_name)
+                        .and_then(|s| s.strip_prefix("0x"))
+                        .and_then(|h| hex::decode(h).ok())
+                        .filter(|salt| salt.len() >= MIN_SALT_LEN)
+                        .ok_or_else(|| {
+                            format!("missing salt of field `{}`", definition.definition_name)
+                        })?;
+                    builder.committed_result(
+                        &definition.definition_type,
+                        &definition.definition_name,
+                        &encoded,
+                        &salt,
+                    )
+                }
+                FieldPrivacy::Encrypted => {
+                    return Err(format!(
+                        "field `{}` is encrypted, fill it with the client",
+                        definition.definition_name
+                    ))
+                }
+            };
+        }
+        for signatory in &self.signatories {
+            builder = builder.signatory(signatory);
+        }
+        if let Some(m) = self.threshold {
+            builder = builder.count_threshold(m);
+        }
+        if self.sequential {
+            builder = builder.signing_order(SigningOrder::Sequential);
+        }
+        if let Some(deadline) = self.sign_deadline {
+            builder = builder.sign_deadline(deadline);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Encodes a value the way the contract compares it: unsigned integers big-endian,
+/// strings as utf-8, bools as one byte and `bytes*` from `0x` hex.
+pub fn encode_value(value_type: &str, value: &serde_yaml::Value) -> Result<Vec<u8>, String> {
+    if value_type.starts_with("uint") {
+        let n: u128 = match value {
+            serde_yaml::Value::Number(n) => n
+                .as_u64()
+                .map(u128::from)
+                .ok_or_else(|| "expected an unsigned integer".to_string())?,
+            serde_yaml::Value::String(s) => s
+                .parse()
+                .map_err(|_| "expected an unsigned integer".to_string())?,
+            _ => return Err("expected an unsigned integer".to_string()),
+        };
+        let bytes = n.to_be_bytes();
+        let first = bytes
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(bytes.len() - 1);
+        return Ok(bytes[first..].to_vec());
+    }
+
+    match (value_type, value) {
+        ("string", serde_yaml::Value::String(s)) => Ok(s.as_bytes().to_vec()),
+        ("bool", serde_yaml::Value::Bool(b)) => Ok(vec![*b as u8]),
+        (t, serde_yaml::Value::String(s)) if t.starts_with("bytes") => s
+            .strip_prefix("0x")
+            .ok_or_else(|| "expected 0x prefixed hex".to_string())
+            .and_then(|h| hex::decode(h).map_err(|err| err.to_string())),
+        (t, _) => Err(format!("unexpected value for `{}`", t)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+name: employment
+description: Employment agreement
+is_revokable: true
+fields:
+  - name: position
+    type: string
+  - name: salary
+    type: uint256
+"#;
+
+    const VALUES: &str = r#"
+recipient: bob.testnet
+signatories: [bob.testnet, alice.testnet]
+values:
+  position: engineer
+  salary: 4096
+"#;
+
+    #[test]
+    fn test_fill_attestation() {
+        let template: SchemaTemplate = serde_yaml::from_str(SCHEMA).unwrap();
+        let schema = template.to_schema("alice.testnet", 1);
+        assert_eq!(schema.schema_definition.len(), 2);
+        assert!(schema.metadata.is_revokable);
+
+        let values: AttestationValues = serde_yaml::from_str(VALUES).unwrap();
+        let attestation = values.to_attestation(&schema, "alice.testnet", 2).unwrap();
+        assert_eq!(
+            attestation.attestation_result[0].value,
+            b"engineer".to_vec()
+        );
+        assert_eq!(attestation.attestation_result[1].value, vec![16, 0]);
+        assert_eq!(
+            attestation.signatories,
+            vec!["alice.testnet", "bob.testnet"]
+        );
+    }
+
+    #[test]
+    fn test_fill_attestation_errors() {
+        let template: SchemaTemplate = serde_yaml::from_str(SCHEMA).unwrap();
+        let schema = template.to_schema("alice.testnet", 1);
+
+        let mut values: AttestationValues = serde_yaml::from_str(VALUES).unwrap();
+        values.values.remove("salary");
+        assert_eq!(
+            values.to_attestation(&schema, "alice.testnet", 2).err(),
+            Some("missing field `salary`".to_string())
+        );
+
+        values
+            .values
+            .insert("bonus".to_string(), serde_yaml::Value::Bool(true));
+        assert_eq!(
+            values.to_attestation(&schema, "alice.testnet", 2).err(),
+            Some("unknown field `bonus`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fill_committed_field() {
+        let mut template: SchemaTemplate = serde_yaml::from_str(SCHEMA).unwrap();
+        template.fields[1].privacy = FieldPrivacy::HashCommitment;
+        let schema = template.to_schema("alice.testnet", 1);
+
+        let mut values: AttestationValues = serde_yaml::from_str(VALUES).unwrap();
+        assert_eq!(
+            values.to_attestation(&schema, "alice.testnet", 2).err(),
+            Some("missing salt of field `salary`".to_string())
+        );
+
+        let salt = [7; 32];
+        values
+            .salts
+            .insert("salary".to_string(), format!("0x{}", hex::encode(salt)));
+        let attestation = values.to_attestation(&schema, "alice.testnet", 2).unwrap();
+        assert!(attestation.verify_field_reveal(&schema, "salary", &salt, &[16, 0]));
+    }
+
+    #[test]
+    fn test_encode_value() {
+        let value = |yaml: &str| serde_yaml::from_str::<serde_yaml::Value>(yaml).unwrap();
+
+        assert_eq!(encode_value("uint256", &value("0")), Ok(vec![0]));
+        assert_eq!(encode_value("uint8", &value("255")), Ok(vec![255]));
+        assert_eq!(
+            encode_value(
+                "uint256",
+                &value("\"340282366920938463463374607431768211455\"")
+            ),
+            Ok(vec![255; 16])
+        );
+        assert_eq!(encode_value("bool", &value("true")), Ok(vec![1]));
+        assert_eq!(
+            encode_value("bytes32", &value("\"0x0aff\"")),
+            Ok(vec![10, 255])
+        );
+        assert!(encode_value("uint256", &value("-1")).is_err());
+        assert!(encode_value("string", &value("1")).is_err());
+    }
+}
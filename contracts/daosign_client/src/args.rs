@@ -0,0 +1,64 @@
+//! JSON arguments of the `daosign_app` methods, e.g. for
+//! `near call <app> store_attestation "$(args)" --accountId <signer>`.
+
+use daosign_attestation::Attestation;
+use daosign_proof_of_signature::{ProofOfRejection, ProofOfSignature};
+use daosign_schema::Schema;
+use serde_json::{json, Value};
+
+pub fn store_schema(s: &Schema) -> Value {
+    json!({ "data": s })
+}
+
+pub fn store_attestation(a: &Attestation) -> Value {
+    json!({ "data": a })
+}
+
+pub fn amend_attestation(a: &Attestation) -> Value {
+    json!({ "data": a })
+}
+
+pub fn store_pos(pos: &ProofOfSignature) -> Value {
+    json!({ "data": pos })
+}
+
+pub fn store_revoke(attestation_id: u128, signature: &[u8]) -> Value {
+    json!({ "a_id": attestation_id, "sig": signature })
+}
+
+pub fn cancel_attestation(attestation_id: u128, signature: &[u8]) -> Value {
+    json!({ "attestation_id": attestation_id, "signature": signature })
+}
+
+pub fn store_rejection(rejection: &ProofOfRejection) -> Value {
+    json!({
+        "attestation_id": rejection.attestation_id,
+        "content_hash": rejection.content_hash,
+        "reason_hash": rejection.reason_hash,
+        "signature": rejection.signature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AttestationBuilder;
+
+    #[test]
+    fn test_args() {
+        let attestation = AttestationBuilder::new(1, "alice.testnet")
+            .signatory("alice.testnet")
+            .build();
+
+        let args = store_attestation(&attestation);
+        assert_eq!(args["data"]["schema_id"], 1);
+        assert_eq!(args["data"]["signatories"], json!(["alice.testnet"]));
+        // Optional fields are left out like in the signed message
+        assert!(args["data"].get("threshold").is_none());
+
+        assert_eq!(
+            store_revoke(2, &[1, 2]),
+            json!({ "a_id": 2, "sig": [1, 2] })
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use daosign_attestation::{
+    disclosure::{results_root, SaltedField},
+    field_commitment, Attestation, AttestationResult, DocumentRef, EncryptedValue, HashAlgorithm,
+    PolicyRef, SignatoryRole, SigningOrder, SigningThreshold,
+};
+use daosign_proof_of_signature::ProofOfSignature;
+use daosign_schema::{
+    FieldPrivacy, PoaMintPolicy, Schema, SchemaDefinition, SchemaMetadata, SignatoryPolicy,
+};
+
+use crate::DaoSignSigner;
+
+/// Builds a `Schema` for `store_schema`.
+pub struct SchemaBuilder {
+    schema: Schema,
+}
+
+impl SchemaBuilder {
+    pub fn new(name: &str, creator: &str) -> Self {
+        Self {
+            schema: Schema {
+                schema_id: 0, // assigned by the contract
+                metadata: SchemaMetadata {
+                    name: name.to_string(),
+                    description: String::new(),
+                    attestation_type: String::new(),
+                    nft_name: String::new(),
+                    nft_symbol: String::new(),
+                    collection_id: String::new(),
+                    creator: creator.to_string(),
+                    created_at: 0,
+                    is_nft: false,
+                    is_public: false,
+                    is_revokable: false,
+                    expire_in: 0,
+                    poa_mint_policy: PoaMintPolicy::default(),
+                },
+                signatory_policy: vec![],
+                schema_definition: vec![],
+                signature: vec![],
+            },
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.schema.metadata.description = description.to_string();
+        self
+    }
+
+    pub fn attestation_type(mut self, attestation_type: &str) -> Self {
+        self.schema.metadata.attestation_type = attestation_type.to_string();
+        self
+    }
+
+    /// Mints the attestations of the schema in a collection named `name`, the app deploys it.
+    pub fn nft(mut self, name: &str, symbol: &str, policy: PoaMintPolicy) -> Self {
+        self.schema.metadata.is_nft = true;
+        self.schema.metadata.nft_name = name.to_string();
+        self.schema.metadata.nft_symbol = symbol.to_string();
+        self.schema.metadata.poa_mint_policy = policy;
+        self
+    }
+
+    pub fn public(mut self, is_public: bool) -> Self {
+        self.schema.metadata.is_public = is_public;
+        self
+    }
+
+    pub fn revokable(mut self, is_revokable: bool) -> Self {
+        self.schema.metadata.is_revokable = is_revokable;
+        self
+    }
+
+    pub fn expire_in(mut self, expire_in: u64) -> Self {
+        self.schema.metadata.expire_in = expire_in;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: u64) -> Self {
+        self.schema.metadata.created_at = created_at;
+        self
+    }
+
+    pub fn field(self, definition_type: &str, definition_name: &str) -> Self {
+        self.private_field(definition_type, definition_name, FieldPrivacy::Plaintext)
+    }
+
+    /// Adds a field whose values are kept on chain as set by `privacy`.
+    pub fn private_field(
+        mut self,
+        definition_type: &str,
+        definition_name: &str,
+        privacy: FieldPrivacy,
+    ) -> Self {
+        self.schema.schema_definition.push(SchemaDefinition {
+            definition_type: definition_type.to_string(),
+            definition_name: definition_name.to_string(),
+            privacy,
+        });
+        self
+    }
+
+    pub fn signatory_policy(mut self, policy: SignatoryPolicy) -> Self {
+        self.schema.signatory_policy.push(policy);
+        self
+    }
+
+    pub fn build(self) -> Schema {
+        self.schema
+    }
+
+    pub fn sign(self, signer: &DaoSignSigner) -> Schema {
+        let mut schema = self.build();
+        signer.sign_schema(&mut schema);
+        schema
+    }
+}
+
+/// Builds an `Attestation` for `store_attestation`. Signatories are put in the canonical
+/// ascending order the contract expects, unless they sign sequentially.
+pub struct AttestationBuilder {
+    attestation: Attestation,
+    signatories: Vec<(String, u64)>, // signatory, weight
+    quorum: Option<u64>,
+    disclosable: bool, // sign the root of the results in `fields_root`
+}
+
+impl AttestationBuilder {
+    pub fn new(schema_id: u128, creator: &str) -> Self {
+        Self {
+            attestation: Attestation {
+                attestation_id: 0, // assigned by the contract
+                schema_id,
+                attestation_result: vec![],
+                creator: creator.to_string(),
+                recipient: String::new(),
+                created_at: 0,
+                signatories: vec![],
+                signature: vec![],
+                is_revoked: false,
+                revoked_at: 0,
+                revoke_signature: vec![],
+                signatory_roles: vec![],
+                threshold: None,
+                signing_order: None,
+                sign_deadline: None,
+                is_cancelled: false,
+                cancelled_at: 0,
+                cancel_signature: vec![],
+                documents: vec![],
+                fields_root: vec![],
+                refers_to: None,
+                supersedes: None,
+                stored_at: 0,
+            },
+            signatories: vec![],
+            quorum: None,
+            disclosable: false,
+        }
+    }
+
+    pub fn recipient(mut self, recipient: &str) -> Self {
+        self.attestation.recipient = recipient.to_string();
+        self
+    }
+
+    pub fn created_at(mut self, created_at: u64) -> Self {
+        self.attestation.created_at = created_at;
+        self
+    }
+
+    pub fn result(mut self, result_type: &str, name: &str, value: Vec<u8>) -> Self {
+        self.attestation.attestation_result.push(AttestationResult {
+            attestation_result_type: result_type.to_string(),
+            name: name.to_string(),
+            value,
+        });
+        self
+    }
+
+    /// Sets a `HashCommitment` field to the commitment of `value`. Keep `salt`, a random value of
+    /// at least 16 bytes, to reveal the value later.
+    pub fn committed_result(
+        self,
+        result_type: &str,
+        name: &str,
+        value: &[u8],
+        salt: &[u8],
+    ) -> Self {
+        self.result(result_type, name, field_commitment(salt, value))
+    }
+
+    /// Sets an `Encrypted` field, `value` must carry a key for the recipient and every signatory.
+    pub fn encrypted_result(self, result_type: &str, name: &str, value: &EncryptedValue) -> Self {
+        self.result(result_type, name, value.encode())
+    }
+
+    /// Sets a field the recipient can later disclose on its own with `disclosure::disclose`: the
+    /// result holds the commitment of `field` and the attestation signs the root of its results.
+    /// Keep `field`, its salt is a random value of at least 16 bytes.
+    pub fn disclosable_result(mut self, result_type: &str, field: &SaltedField) -> Self {
+        self.disclosable = true;
+        self.result(result_type, &field.name, field.commitment())
+    }
+
+    /// Anchors an off-chain document, `location` is e.g. its IPFS CID, Arweave tx or URL.
+    pub fn document(
+        mut self,
+        name: &str,
+        hash: Vec<u8>,
+        hash_algorithm: HashAlgorithm,
+        location: Option<&str>,
+    ) -> Self {
+        self.attestation.documents.push(DocumentRef {
+            name: name.to_string(),
+            hash,
+            hash_algorithm,
+            location: location.map(str::to_string),
+        });
+        self
+    }
+
+    pub fn signatory(self, signatory: &str) -> Self {
+        self.weighted_signatory(signatory, 1)
+    }
+
+    /// Adds a signatory counting `weight` towards the quorum.
+    pub fn weighted_signatory(mut self, signatory: &str, weight: u64) -> Self {
+        self.signatories.push((signatory.to_string(), weight));
+        self
+    }
+
+    /// Binds the signatory to a signatory policy of the schema.
+    pub fn signatory_role(mut self, signatory: &str, policy: PolicyRef) -> Self {
+        self.attestation.signatory_roles.push(SignatoryRole {
+            signatory: signatory.to_string(),
+            policy,
+        });
+        self
+    }
+
+    /// Forms the agreement once `m` signatories signed.
+    pub fn count_threshold(mut self, m: u32) -> Self {
+        self.attestation.threshold = Some(SigningThreshold::Count(m));
+        self.quorum = None;
+        self
+    }
+
+    /// Forms the agreement once the weight of the signatories who signed reaches `quorum`.
+    pub fn quorum(mut self, quorum: u64) -> Self {
+        self.quorum = Some(quorum);
+        self
+    }
+
+    pub fn signing_order(mut self, signing_order: SigningOrder) -> Self {
+        self.attestation.signing_order = Some(signing_order);
+        self
+    }
+
+    pub fn sign_deadline(mut self, sign_deadline: u64) -> Self {
+        self.attestation.sign_deadline = Some(sign_deadline);
+        self
+    }
+
+    /// Links the attestation to another one, e.g. an invoice to its agreement.
+    pub fn refers_to(mut self, attestation_id: u128) -> Self {
+        self.attestation.refers_to = Some(attestation_id);
+        self
+    }
+
+    /// Makes the attestation an amendment of `attestation_id`, submitted with `amend_attestation`.
+    /// It needs the signatories and signing rules of the amended attestation.
+    pub fn supersedes(mut self, attestation_id: u128) -> Self {
+        self.attestation.supersedes = Some(attestation_id);
+        self
+    }
+
+    pub fn build(self) -> Attestation {
+        let mut attestation = self.attestation;
+        let mut signatories = self.signatories;
+        if attestation.signing_order != Some(SigningOrder::Sequential) {
+            signatories.sort();
+        }
+
+        if let Some(quorum) = self.quorum {
+            attestation.threshold = Some(SigningThreshold::Weighted {
+                weights: signatories.iter().map(|(_, weight)| *weight).collect(),
+                quorum,
+            });
+        }
+        attestation.signatories = signatories
+            .into_iter()
+            .map(|(signatory, _)| signatory)
+            .collect();
+        if self.disclosable {
+            attestation.fields_root = results_root(&attestation.attestation_result);
+        }
+        attestation
+    }
+
+    pub fn sign(self, signer: &DaoSignSigner) -> Attestation {
+        let mut attestation = self.build();
+        signer.sign_attestation(&mut attestation);
+        attestation
+    }
+}
+
+/// Builds a `ProofOfSignature` for `store_pos` over an attestation as stored by the contract.
+pub struct ProofOfSignatureBuilder {
+    pos: ProofOfSignature,
+}
+
+impl ProofOfSignatureBuilder {
+    pub fn new(a: &Attestation, signer_id: &str) -> Self {
+        Self {
+            pos: ProofOfSignature {
+                attestation_id: a.attestation_id,
+                creator: signer_id.to_string(),
+                created_at: 0, // recorded by the contract
+                content_hash: a.content_hash(),
+                signed_at: 0,
+                public_key: vec![], // recorded by the contract
+                signature: vec![],
+            },
+        }
+    }
+
+    /// Signing time in nanoseconds, it can't be ahead of the block time.
+    pub fn signed_at(mut self, signed_at: u64) -> Self {
+        self.pos.signed_at = signed_at;
+        self
+    }
+
+    pub fn build(self) -> ProofOfSignature {
+        self.pos
+    }
+
+    pub fn sign(self, signer: &DaoSignSigner) -> ProofOfSignature {
+        let mut pos = self.build();
+        signer.sign_pos(&mut pos);
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use daosign_verify::{verify_attestation, verify_pos, verify_schema};
+    use ed25519_dalek::Keypair;
+    use rand::rngs::OsRng;
+
+    fn create_signer(account_id: &str) -> DaoSignSigner {
+        let mut csprng = OsRng {};
+        DaoSignSigner::new(account_id, Keypair::generate(&mut csprng))
+    }
+
+    #[test]
+    fn test_signed_records() {
+        let alice = create_signer("alice.testnet");
+        let bob = create_signer("bob.testnet");
+
+        let schema = SchemaBuilder::new("employment", alice.account_id())
+            .field("uint256", "salary")
+            .revokable(true)
+            .sign(&alice);
+        assert_eq!(verify_schema(&schema, &alice.public_key()), Ok(()));
+
+        let attestation = AttestationBuilder::new(0, alice.account_id())
+            .result("uint256", "salary", vec![16, 0])
+            .signatory(bob.account_id())
+            .signatory(alice.account_id())
+            .sign(&alice);
+        assert_eq!(
+            verify_attestation(&attestation, &alice.public_key()),
+            Ok(())
+        );
+
+        let pos = ProofOfSignatureBuilder::new(&attestation, bob.account_id())
+            .signed_at(10)
+            .sign(&bob);
+        assert_eq!(verify_pos(&pos, &attestation, &bob.public_key()), Ok(()));
+    }
+
+    #[test]
+    fn test_canonical_signatories() {
+        let attestation = AttestationBuilder::new(0, "alice.testnet")
+            .weighted_signatory("carol.testnet", 1)
+            .weighted_signatory("alice.testnet", 3)
+            .weighted_signatory("bob.testnet", 2)
+            .quorum(4)
+            .build();
+        assert_eq!(
+            attestation.signatories,
+            vec!["alice.testnet", "bob.testnet", "carol.testnet"]
+        );
+        // Weights follow their signatories
+        assert_eq!(
+            attestation.threshold,
+            Some(SigningThreshold::Weighted {
+                weights: vec![3, 2, 1],
+                quorum: 4
+            })
+        );
+
+        let attestation = AttestationBuilder::new(0, "alice.testnet")
+            .signatory("carol.testnet")
+            .signatory("alice.testnet")
+            .signing_order(SigningOrder::Sequential)
+            .build();
+        assert_eq!(
+            attestation.signatories,
+            vec!["carol.testnet", "alice.testnet"]
+        );
+    }
+}
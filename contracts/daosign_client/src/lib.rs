@@ -0,0 +1,28 @@
+//! Client side helpers to build, sign and submit DAOsign records.
+//!
+//! ```ignore
+//! let signer = DaoSignSigner::from_credentials_file("~/.near-credentials/testnet/alice.testnet.json")?;
+//! let attestation = AttestationBuilder::new(0, signer.account_id())
+//!     .result("uint256", "salary", vec![16, 0])
+//!     .signatory("bob.testnet")
+//!     .sign(&signer);
+//! println!("{}", args::store_attestation(&attestation));
+//! ```
+
+pub mod args;
+mod builder;
+mod signer;
+
+pub use builder::{AttestationBuilder, ProofOfSignatureBuilder, SchemaBuilder};
+pub use signer::{decode_public_key, ClientError, DaoSignSigner};
+
+pub use daosign_attestation::{
+    disclosure::{self, FieldDisclosure, SaltedField},
+    field_commitment, Attestation, AttestationResult, DocumentRef, EncryptedValue, HashAlgorithm,
+    PolicyRef, SignatoryRole, SigningOrder, SigningThreshold, WrappedKey, MIN_SALT_LEN,
+};
+pub use daosign_proof_of_signature::{ProofOfRejection, ProofOfSignature};
+pub use daosign_schema::{
+    FieldPrivacy, PoaMintPolicy, PolicyOperator, Schema, SchemaDefinition, SchemaMetadata,
+    SignatoryPolicy,
+};
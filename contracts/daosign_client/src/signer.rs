@@ -0,0 +1,229 @@
+use std::{fmt, fs, path::Path};
+
+use daosign_attestation::Attestation;
+use daosign_proof_of_signature::{ProofOfRejection, ProofOfSignature};
+use daosign_schema::Schema;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use serde::Deserialize;
+
+const ED25519_PREFIX: &str = "ed25519:";
+
+/// Error raised while loading keys.
+#[derive(Debug)]
+pub enum ClientError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    InvalidKey(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(err) => write!(f, "failed to read credentials: {}", err),
+            ClientError::Json(err) => write!(f, "invalid credentials file: {}", err),
+            ClientError::InvalidKey(reason) => write!(f, "invalid key: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(err: serde_json::Error) -> Self {
+        ClientError::Json(err)
+    }
+}
+
+/// Decodes an `ed25519:<base58>` public key to its raw 32 bytes.
+pub fn decode_public_key(public_key: &str) -> Result<Vec<u8>, ClientError> {
+    let encoded = public_key
+        .strip_prefix(ED25519_PREFIX)
+        .ok_or_else(|| ClientError::InvalidKey("expected an ed25519 key".to_string()))?;
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|err| ClientError::InvalidKey(err.to_string()))?;
+    PublicKey::from_bytes(&bytes).map_err(|err| ClientError::InvalidKey(err.to_string()))?;
+    Ok(bytes)
+}
+
+/// Credentials file written by near-cli, e.g. `~/.near-credentials/testnet/<account>.json`.
+#[derive(Deserialize)]
+struct NearCredentials {
+    account_id: String,
+    #[serde(alias = "secret_key")]
+    private_key: String,
+}
+
+/// Account signing DAOsign records with one of its ed25519 access keys.
+pub struct DaoSignSigner {
+    account_id: String,
+    keypair: Keypair,
+}
+
+impl DaoSignSigner {
+    pub fn new(account_id: &str, keypair: Keypair) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            keypair,
+        }
+    }
+
+    /// Loads the signer from a near-cli credentials file.
+    pub fn from_credentials_file(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        Self::from_credentials_json(&fs::read_to_string(path)?)
+    }
+
+    /// Loads the signer from the content of a near-cli credentials file.
+    pub fn from_credentials_json(json: &str) -> Result<Self, ClientError> {
+        let credentials: NearCredentials = serde_json::from_str(json)?;
+        Self::from_secret_key(&credentials.account_id, &credentials.private_key)
+    }
+
+    /// Loads the signer from an `ed25519:<base58>` secret key, either the 64 byte keypair
+    /// near-cli stores or a bare 32 byte seed.
+    pub fn from_secret_key(account_id: &str, secret_key: &str) -> Result<Self, ClientError> {
+        let encoded = secret_key
+            .strip_prefix(ED25519_PREFIX)
+            .ok_or_else(|| ClientError::InvalidKey("expected an ed25519 key".to_string()))?;
+        let bytes = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|err| ClientError::InvalidKey(err.to_string()))?;
+
+        let keypair = match bytes.len() {
+            64 => Keypair::from_bytes(&bytes)
+                .map_err(|err| ClientError::InvalidKey(err.to_string()))?,
+            32 => {
+                let secret = SecretKey::from_bytes(&bytes)
+                    .map_err(|err| ClientError::InvalidKey(err.to_string()))?;
+                let public = PublicKey::from(&secret);
+                Keypair { secret, public }
+            }
+            len => {
+                return Err(ClientError::InvalidKey(format!(
+                    "unexpected key length {}",
+                    len
+                )))
+            }
+        };
+        Ok(Self::new(account_id, keypair))
+    }
+
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// Raw 32 byte public key.
+    pub fn public_key(&self) -> Vec<u8> {
+        self.keypair.public.to_bytes().to_vec()
+    }
+
+    /// Public key in the `ed25519:<base58>` format of NEAR access keys.
+    pub fn public_key_str(&self) -> String {
+        format!(
+            "{}{}",
+            ED25519_PREFIX,
+            bs58::encode(self.keypair.public.to_bytes()).into_string()
+        )
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.keypair.sign(message).to_bytes().to_vec()
+    }
+
+    pub fn sign_schema(&self, s: &mut Schema) {
+        s.signature = self.sign(&s.to_ed25519_message());
+    }
+
+    pub fn sign_attestation(&self, a: &mut Attestation) {
+        a.signature = self.sign(&a.to_ed25519_message());
+    }
+
+    pub fn sign_pos(&self, pos: &mut ProofOfSignature) {
+        pos.signature = self.sign(&pos.to_ed25519_message());
+    }
+
+    pub fn sign_rejection(&self, rejection: &mut ProofOfRejection) {
+        rejection.signature = self.sign(&rejection.to_ed25519_message());
+    }
+
+    /// Signature of `store_revoke`.
+    pub fn sign_revoke(&self, a: &Attestation) -> Vec<u8> {
+        self.sign(&a.to_ed25519_message_revoke())
+    }
+
+    /// Signature of `cancel_attestation`.
+    pub fn sign_cancel(&self, a: &Attestation) -> Vec<u8> {
+        self.sign(&a.to_ed25519_message_cancel())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn create_keypair() -> Keypair {
+        let mut csprng = OsRng {};
+        Keypair::generate(&mut csprng)
+    }
+
+    #[test]
+    fn test_credentials_json() {
+        let keypair = create_keypair();
+        let json = format!(
+            r#"{{"account_id":"alice.testnet","public_key":"ed25519:{}","private_key":"ed25519:{}"}}"#,
+            bs58::encode(keypair.public.to_bytes()).into_string(),
+            bs58::encode(keypair.to_bytes()).into_string()
+        );
+
+        let signer = DaoSignSigner::from_credentials_json(&json).unwrap();
+        assert_eq!(signer.account_id(), "alice.testnet");
+        assert_eq!(signer.public_key(), keypair.public.to_bytes().to_vec());
+        assert_eq!(
+            signer.public_key_str(),
+            format!(
+                "ed25519:{}",
+                bs58::encode(keypair.public.to_bytes()).into_string()
+            )
+        );
+
+        // The seed alone gives the same key
+        let seed = format!(
+            "ed25519:{}",
+            bs58::encode(keypair.secret.to_bytes()).into_string()
+        );
+        let signer = DaoSignSigner::from_secret_key("alice.testnet", &seed).unwrap();
+        assert_eq!(signer.public_key(), keypair.public.to_bytes().to_vec());
+
+        assert_eq!(
+            decode_public_key(&signer.public_key_str()).unwrap(),
+            signer.public_key()
+        );
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert!(matches!(
+            DaoSignSigner::from_secret_key("alice.testnet", "secp256k1:abc"),
+            Err(ClientError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            DaoSignSigner::from_secret_key("alice.testnet", "ed25519:0OIl"),
+            Err(ClientError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            DaoSignSigner::from_secret_key("alice.testnet", "ed25519:abc"),
+            Err(ClientError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            DaoSignSigner::from_credentials_json("{}"),
+            Err(ClientError::Json(_))
+        ));
+    }
+}
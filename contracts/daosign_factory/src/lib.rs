@@ -5,11 +5,13 @@ mod daosign_factory {
         env, log, near_bindgen,
         serde::{Deserialize, Serialize},
         serde_json::json,
-        AccountId, Gas, NearToken, Promise,
+        AccountId, Gas, NearToken, Promise, PromiseError,
     };
+    use std::collections::{HashMap, HashSet};
     const FT_WASM_CODE: &[u8] = include_bytes!("../../../res/non_fungible_token.wasm");
     const NEAR_PER_STORAGE: NearToken = NearToken::from_yoctonear(10u128.pow(19)); // 10e19yⓃ
     const TGAS: Gas = Gas::from_tgas(5); // Adjusted to standard usage
+    const CALLBACK_GAS: Gas = Gas::from_tgas(10); // Gas reserved for `on_deploy_nft`
     const NO_DEPOSIT: NearToken = NearToken::from_near(0); // 0yⓃ
 
     #[near_bindgen]
@@ -24,23 +26,95 @@ mod daosign_factory {
         PartialEq,
         Eq,
     )]
-    pub struct DaoSignFactory {}
+    pub struct DaoSignFactory {
+        // Apps allowed to deploy collections
+        pub apps: HashSet<AccountId>,
+        // Successfully deployed collections
+        pub deployments: HashMap<AccountId, u128>, // nft collection contract => schema_id
+    }
 
     #[near_bindgen]
     impl DaoSignFactory {
         #[init]
         pub fn new() -> Self {
-            Self {}
+            Self {
+                apps: HashSet::new(),
+                deployments: HashMap::new(),
+            }
+        }
+
+        /// # Migration from the state of the first deployed version.
+        ///
+        /// The first version kept no state, the deployments it made before the upgrade are not recorded
+        /// and the apps allowed to deploy collections are added again with `add_app`.
+        ///
+        /// # Returns
+        ///
+        /// The migrated DaoSignFactory.
+        #[private]
+        #[init(ignore_state)]
+        pub fn migrate() -> Self {
+            Self::new()
+        }
+
+        /// # Message to allow an app to deploy collections.
+        ///
+        /// # Arguments
+        ///
+        /// * `app_id` - Account of the `daosign_app` contract.
+        #[private]
+        pub fn add_app(&mut self, app_id: AccountId) {
+            self.apps.insert(app_id.clone());
+            log!("Event: AppAdded {{ app: {} }}", app_id);
+        }
+
+        /// # Message to stop an app from deploying collections.
+        ///
+        /// # Arguments
+        ///
+        /// * `app_id` - Account of the `daosign_app` contract.
+        #[private]
+        pub fn remove_app(&mut self, app_id: AccountId) {
+            self.apps.remove(&app_id);
+            log!("Event: AppRemoved {{ app: {} }}", app_id);
         }
 
+        /// # Message to check whether an app can deploy collections.
+        ///
+        /// # Arguments
+        ///
+        /// * `app_id` - Account of the `daosign_app` contract.
+        pub fn is_app(&self, app_id: AccountId) -> bool {
+            self.apps.contains(&app_id)
+        }
+
+        /// # Message to deploy the nft collection of a schema.
+        ///
+        /// Only the apps added with `add_app` deploy collections, so nobody else can take the
+        /// account an app names its collection after.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - Schema the collection is deployed for.
+        /// * `nft_acc` - Account of the collection, a subaccount of the factory.
         #[payable] // Allows NEAR deposit
         pub fn deploy_nft(&mut self, schema_id: u128, nft_acc: String) -> Promise {
             let current_account = env::current_account_id();
             let code = FT_WASM_CODE;
 
+            assert!(
+                self.apps.contains(&env::predecessor_account_id()),
+                "unauthorized app!"
+            );
+
             // Convert the NFT account string into an AccountId
             let nft_acc: AccountId = nft_acc.parse().expect("Invalid account ID");
 
+            assert!(
+                !self.deployments.contains_key(&nft_acc),
+                "collection already deployed!"
+            );
+
             // Calculate storage cost
             let contract_bytes = code.len() as u128;
             let contract_storage_cost = NEAR_PER_STORAGE.saturating_mul(contract_bytes);
@@ -55,9 +129,10 @@ mod daosign_factory {
                 minimum_needed
             );
 
+            // The collection is owned by the account requesting the deployment (the app)
             let nft_args = Self::get_nft_metadata();
             let args = json!({
-                "owner_id": current_account,
+                "owner_id": env::predecessor_account_id(),
                 "metadata": nft_args
             })
             .to_string()
@@ -70,10 +145,77 @@ mod daosign_factory {
             );
 
             Promise::new(nft_acc.clone())
-            .create_account()
-            .transfer(attached_deposit) // Use the attached deposit
-            .deploy_contract(code.to_vec())
-            .function_call("new".to_owned(), args, NO_DEPOSIT, TGAS)
+                .create_account()
+                .transfer(attached_deposit) // Use the attached deposit
+                .deploy_contract(code.to_vec())
+                .function_call("new".to_owned(), args, NO_DEPOSIT, TGAS)
+                .then(
+                    Self::ext(current_account)
+                        .with_static_gas(CALLBACK_GAS)
+                        .on_deploy_nft(
+                            schema_id,
+                            nft_acc,
+                            env::predecessor_account_id(),
+                            attached_deposit,
+                        ),
+                )
+        }
+
+        /// # Callback resolving the result of `deploy_nft`.
+        ///
+        /// Records the collection if the subaccount was created and initialized, otherwise
+        /// refunds the attached deposit to the account that requested the deployment.
+        ///
+        /// # Arguments
+        ///
+        /// * `schema_id` - Schema the collection was deployed for.
+        /// * `nft_acc` - Account of the deployed collection.
+        /// * `predecessor` - Account that requested the deployment.
+        /// * `deposit` - Deposit attached to `deploy_nft`.
+        ///
+        /// # Returns
+        ///
+        /// `true` if the collection was deployed.
+        #[private]
+        pub fn on_deploy_nft(
+            &mut self,
+            schema_id: u128,
+            nft_acc: AccountId,
+            predecessor: AccountId,
+            deposit: NearToken,
+            #[callback_result] call_result: Result<(), PromiseError>,
+        ) -> bool {
+            if call_result.is_err() {
+                // The whole batch is reverted, so the deposit is back on the factory account
+                Promise::new(predecessor.clone()).transfer(deposit);
+
+                log!(
+                    "Event: CollectionDeploymentFailed {{ schema_id: {}, collection: {}, refund_to: {} }}",
+                    schema_id,
+                    nft_acc,
+                    predecessor
+                );
+                return false;
+            }
+
+            self.deployments.insert(nft_acc.clone(), schema_id);
+
+            log!(
+                "Event: CollectionDeployed {{ schema_id: {}, collection: {}, owner: {} }}",
+                schema_id,
+                nft_acc,
+                predecessor
+            );
+            true
+        }
+
+        /// # Message to retrieve the schema id of a deployed collection.
+        ///
+        /// # Arguments
+        ///
+        /// * `nft_acc` - Account of the deployed collection.
+        pub fn get_deployment(&self, nft_acc: AccountId) -> Option<u128> {
+            self.deployments.get(&nft_acc).copied()
         }
 
         pub fn get_nft_metadata() -> NFTContractMetadata {
@@ -89,3 +231,151 @@ mod daosign_factory {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use daosign_factory::DaoSignFactory;
+    use near_sdk::{
+        env,
+        test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder},
+        testing_env, AccountId, NearToken, PromiseError, VMContext,
+    };
+    use std::any::Any;
+
+    fn collection() -> AccountId {
+        "schema-0.factory.testnet".parse().expect("Invalid address")
+    }
+
+    fn update_states() {
+        let context: VMContext = VMContextBuilder::new()
+            .current_account_id("factory.testnet".parse().unwrap())
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_near(10))
+            .build();
+        testing_env!(context);
+    }
+
+    /// Utility method to check if the result contains the expected error message.
+    fn check_error(msg: &str, result: Result<(), Box<dyn Any + Send>>) -> bool {
+        if let Err(err) = result {
+            if let Some(err_msg) = err.downcast_ref::<String>() {
+                assert!(
+                    err_msg.contains(msg),
+                    "Unexpected error message: {}",
+                    err_msg
+                );
+                true
+            } else if let Some(err_msg) = err.downcast_ref::<&str>() {
+                assert!(
+                    err_msg.contains(msg),
+                    "Unexpected error message: {}",
+                    err_msg
+                );
+                true
+            } else {
+                panic!("Error occurred, but it was not a String or &str type.");
+            }
+        } else {
+            panic!("No error occurred, but an error was expected.");
+        }
+    }
+
+    #[test]
+    fn test_on_deploy_nft() {
+        update_states();
+        let mut factory = DaoSignFactory::new();
+
+        let deployed = factory.on_deploy_nft(
+            0,
+            collection(),
+            accounts(0),
+            NearToken::from_near(10),
+            Ok(()),
+        );
+
+        assert!(deployed);
+        assert_eq!(factory.get_deployment(collection()), Some(0));
+        // The collection is owned by the app that requested it
+        assert!(get_logs()[0].contains(&format!("owner: {}", accounts(0))));
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_on_deploy_nft_failed() {
+        update_states();
+        let mut factory = DaoSignFactory::new();
+
+        let deployed = factory.on_deploy_nft(
+            0,
+            collection(),
+            accounts(0),
+            NearToken::from_near(10),
+            Err(PromiseError::Failed),
+        );
+
+        assert!(!deployed);
+        assert_eq!(factory.get_deployment(collection()), None);
+
+        // The deposit is refunded to the app
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(0));
+        assert!(get_logs()[0].contains("CollectionDeploymentFailed"));
+    }
+
+    #[test]
+    fn test_migrate() {
+        update_states();
+        // The first version stored an empty struct
+        env::state_write(&());
+
+        let factory = DaoSignFactory::migrate();
+        assert_eq!(factory, DaoSignFactory::new());
+    }
+
+    #[test]
+    fn test_add_app() {
+        update_states();
+        let mut factory = DaoSignFactory::new();
+
+        factory.add_app(accounts(0));
+        assert!(factory.is_app(accounts(0)));
+
+        factory.remove_app(accounts(0));
+        assert!(!factory.is_app(accounts(0)));
+    }
+
+    #[test]
+    fn test_deploy_nft_unauthorized_app() {
+        update_states();
+
+        let result = std::panic::catch_unwind(|| {
+            let mut factory = DaoSignFactory::new();
+            factory.add_app(accounts(1));
+            factory.deploy_nft(0, collection().to_string());
+        });
+
+        assert!(
+            check_error("unauthorized app!", result),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_deploy_nft_already_deployed() {
+        update_states();
+
+        let result = std::panic::catch_unwind(|| {
+            let mut factory = DaoSignFactory::new();
+            factory.add_app(accounts(0));
+            factory.deployments.insert(collection(), 0);
+            factory.deploy_nft(0, collection().to_string());
+        });
+
+        assert!(
+            check_error("collection already deployed!", result),
+            "Unexpected error message"
+        );
+    }
+}
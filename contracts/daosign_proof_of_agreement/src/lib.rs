@@ -1,10 +1,13 @@
+use daosign_ed25519::recover;
+use daosign_proof_of_signature::ProofOfSignature;
+use ed25519_dalek::{PublicKey, Signature};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 // use near_sdk::near_bindgen;
 use serde::{Deserialize, Serialize};
 
 use schemars::JsonSchema;
 
-/// ProofOfSignature struct representing the Proof-of-Signature parameters.
+/// ProofOfAgreement struct representing the Proof-of-Agreement parameters.
 // #[near_bindgen]
 #[derive(
     BorshDeserialize,
@@ -19,5 +22,124 @@ use schemars::JsonSchema;
 )]
 pub struct ProofOfAgreement {
     pub attestation_id: u128,
-    pub signatures: Vec<Vec<u8>>,
+    pub content_hash: Vec<u8>, // `Attestation::content_hash` every signer committed to
+    pub completed_at: u64,     // block height the agreement was formed at
+    pub signatures: Vec<AgreementSignature>,
+}
+
+/// Signature of one signer of the agreement.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct AgreementSignature {
+    pub account_id: String,
+    pub public_key: Vec<u8>, // ed25519 key the signer signed with
+    pub signature: Vec<u8>,
+    pub signed_at: u64,
+}
+
+impl AgreementSignature {
+    /// Proof of Signature message the signer signed.
+    pub fn to_ed25519_message(&self, attestation_id: u128, content_hash: &[u8]) -> Vec<u8> {
+        ProofOfSignature {
+            attestation_id,
+            creator: self.account_id.clone(),
+            created_at: 0,
+            content_hash: content_hash.to_vec(),
+            signed_at: self.signed_at,
+            public_key: vec![],
+            signature: vec![],
+        }
+        .to_ed25519_message()
+    }
+}
+
+impl ProofOfAgreement {
+    /// Re-verifies every signature of the agreement against the signer's public key.
+    /// Whether a key belongs to its account is checked against the chain's access keys.
+    pub fn verify(&self) -> bool {
+        if self.signatures.is_empty() {
+            return false;
+        }
+
+        self.signatures.iter().enumerate().all(|(i, s)| {
+            // Each account signs once
+            if self.signatures[..i]
+                .iter()
+                .any(|other| other.account_id == s.account_id)
+            {
+                return false;
+            }
+
+            let (public_key, signature) = match (
+                PublicKey::from_bytes(&s.public_key),
+                Signature::from_bytes(&s.signature),
+            ) {
+                (Ok(public_key), Ok(signature)) => (public_key, signature),
+                _ => return false,
+            };
+            recover(
+                public_key,
+                signature,
+                &s.to_ed25519_message(self.attestation_id, &self.content_hash),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    fn create_signer() -> Keypair {
+        let mut csprng = OsRng {};
+        Keypair::generate(&mut csprng)
+    }
+
+    fn sign_agreement(account_id: &str, signer: &Keypair) -> AgreementSignature {
+        let mut s = AgreementSignature {
+            account_id: String::from(account_id),
+            public_key: signer.public.to_bytes().to_vec(),
+            signature: vec![],
+            signed_at: 10,
+        };
+        s.signature = signer
+            .sign(&s.to_ed25519_message(0, &[1; 32]))
+            .to_bytes()
+            .to_vec();
+        s
+    }
+
+    #[test]
+    fn check_poa() {
+        let mut poa = ProofOfAgreement {
+            attestation_id: 0,
+            content_hash: vec![1; 32],
+            completed_at: 100,
+            signatures: vec![
+                sign_agreement("employer.testnet", &create_signer()),
+                sign_agreement("employee.testnet", &create_signer()),
+            ],
+        };
+        assert!(poa.verify());
+
+        // The signatures commit to the content
+        poa.content_hash = vec![2; 32];
+        assert!(!poa.verify());
+        poa.content_hash = vec![1; 32];
+
+        // and to the signer
+        poa.signatures[1].account_id = String::from("employer.testnet");
+        assert!(!poa.verify());
+    }
 }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use daosign_attestation::Attestation;
 use daosign_ed25519::recover;
-use daosign_schema::{Schema, SignatoryPolicy};
+use daosign_schema::{AttestationRequirement, Schema, SignatoryPolicy};
 use ed25519_dalek::{PublicKey, Signature};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
@@ -28,7 +28,11 @@ use serde_json;
 pub struct ProofOfSignature {
     pub attestation_id: u128,
     pub creator: String,
-    pub created_at: u64,
+    pub created_at: u64,       // block timestamp the signature was stored at
+    pub content_hash: Vec<u8>, // `Attestation::content_hash` of the signed attestation
+    pub signed_at: u64,        // timestamp claimed by the signer
+    #[serde(default)]
+    pub public_key: Vec<u8>, // ed25519 key of the signer, recorded by the contract
     pub signature: Vec<u8>,
 }
 
@@ -46,6 +50,56 @@ pub struct ProofOfSignature {
 pub struct ProofOfSignatureMessage {
     pub attestation_id: u128,
     pub creator: String,
+    pub content_hash: Vec<u8>,
+    pub signed_at: u64,
+}
+
+/// ProofOfRejection struct representing a signatory's signed refusal of an Attestation.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct ProofOfRejection {
+    pub attestation_id: u128,
+    pub creator: String,
+    pub content_hash: Vec<u8>, // `Attestation::content_hash` of the rejected attestation
+    pub reason_hash: Vec<u8>,  // sha256 of the off-chain reason
+    pub created_at: u64,
+    pub signature: Vec<u8>,
+}
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct ProofOfRejectionMessage {
+    pub attestation_id: u128,
+    pub creator: String,
+    pub content_hash: Vec<u8>,
+    pub reason_hash: Vec<u8>,
+    pub action: String,
+}
+
+/// Attestation records signatory policies are evaluated against.
+pub struct AttestationRecords<'a> {
+    pub user_attestation: &'a HashMap<u128, HashMap<String, Vec<Attestation>>>, // schema_id => user => Attestation[]
+    pub attestations: &'a HashMap<u128, Attestation>, // attestation_id => Attestation
+    pub schemas: &'a HashMap<u128, Schema>,           // schema_id => Schema
+    pub superseded_by: &'a HashMap<u128, u128>,       // attestation_id => amendment in effect
 }
 
 impl ProofOfSignature {
@@ -53,6 +107,8 @@ impl ProofOfSignature {
         let pos = ProofOfSignatureMessage {
             attestation_id: self.attestation_id,
             creator: self.creator.clone(),
+            content_hash: self.content_hash.clone(),
+            signed_at: self.signed_at,
         };
         // Serialize the message to JSON and convert to bytes
         serde_json::to_vec(&pos).expect("Failed to serialize message") // directly return the serialized vector
@@ -62,17 +118,96 @@ impl ProofOfSignature {
         &self,
         a: Attestation,
         s: Schema,
-        user_a: &HashMap<u128, HashMap<String, Vec<Attestation>>>,
+        records: &AttestationRecords,
+        signed: &[String],
+        rejected: &[String],
     ) {
         assert!(!a.is_revoked, "attestation revoked.");
+        assert!(!a.is_cancelled, "attestation cancelled.");
+        assert!(
+            !a.is_deadline_passed(env::block_timestamp()),
+            "sign deadline passed."
+        );
 
         assert!(
-            is_signatory(a.signatories, env::signer_account_id()),
+            is_signatory(a.signatories.clone(), env::signer_account_id()),
             "Invalid signatory address."
         );
         let caller_id = env::signer_account_id();
+        assert!(
+            self.creator == caller_id.as_str(),
+            "creator is not the signer."
+        );
+
+        // Signatories sign stage by stage if the attestation sets a signing order
+        assert!(
+            a.next_expected_signers(signed, rejected)
+                .contains(&String::from(caller_id.as_str())),
+            "out of order signature."
+        );
+
+        // Only the policy of the caller's role applies, if the attestation binds one
+        let policies = a.policies_for(&s, caller_id.as_str());
+        validate_signatory_policy(policies, records, caller_id.clone());
 
-        validate_signatory_policy(s, user_a, caller_id.clone());
+        // The signature commits to the current content of the attestation
+        assert!(
+            self.content_hash == a.content_hash(),
+            "content hash mismatch."
+        );
+        assert!(
+            self.signed_at <= env::block_timestamp(),
+            "invalid signing time."
+        );
+
+        let signature = Signature::from_bytes(&self.signature).expect("Invalid signature");
+
+        let caller_pk = env::signer_account_pk(); // ✅ Extract raw bytes from `near_sdk::PublicKey`
+
+        // ✅ Ensure it's 33 bytes and remove the first byte (prefix)
+        let ed25519_bytes = &caller_pk.as_bytes()[1..]; // Extract only the last 32 bytes
+        let caller =
+            PublicKey::from_bytes(ed25519_bytes).expect("❌ Failed to parse Dalek PublicKey");
+
+        assert!(
+            recover(caller, signature, &self.to_ed25519_message()),
+            "invalid signature"
+        );
+    }
+}
+
+impl ProofOfRejection {
+    pub fn to_ed25519_message(&self) -> Vec<u8> {
+        let rejection = ProofOfRejectionMessage {
+            attestation_id: self.attestation_id,
+            creator: self.creator.clone(),
+            content_hash: self.content_hash.clone(),
+            reason_hash: self.reason_hash.clone(),
+            action: String::from("reject"),
+        };
+        // Serialize the message to JSON and convert to bytes
+        serde_json::to_vec(&rejection).expect("Failed to serialize message") // directly return the serialized vector
+    }
+
+    pub fn validate(&self, a: Attestation) {
+        assert!(!a.is_revoked, "attestation revoked.");
+        assert!(!a.is_cancelled, "attestation cancelled.");
+        assert!(
+            !a.is_deadline_passed(env::block_timestamp()),
+            "sign deadline passed."
+        );
+
+        assert!(
+            is_signatory(a.signatories.clone(), env::signer_account_id()),
+            "Invalid signatory address."
+        );
+        assert!(self.reason_hash.len() == 32, "invalid reason hash.");
+
+        // The rejection refers to the current content of the attestation
+        assert!(
+            self.content_hash == a.content_hash(),
+            "content hash mismatch."
+        );
 
         let signature = Signature::from_bytes(&self.signature).expect("Invalid signature");
 
@@ -102,66 +237,50 @@ fn is_signatory(signatories: Vec<String>, caller: AccountId) -> bool {
 }
 
 fn validate_signatory_policy(
-    s: Schema,
-    user_a: &HashMap<u128, HashMap<String, Vec<Attestation>>>,
+    policies: Vec<SignatoryPolicy>,
+    records: &AttestationRecords,
     signer: AccountId,
 ) {
-    let policy_count = s.signatory_policy.len();
+    let policy_count = policies.len();
 
     if policy_count == 0 {
         return;
     }
 
-    for policy in s.signatory_policy {
-        let is_satisfied = is_policy_satisfied(policy, user_a, signer.clone());
+    for policy in policies {
+        let is_satisfied = is_policy_satisfied(policy, records, signer.clone());
         assert!(is_satisfied, "insufficient attestations.")
     }
 }
 
 fn is_policy_satisfied(
     policy: SignatoryPolicy,
-    user_a: &HashMap<u128, HashMap<String, Vec<Attestation>>>,
+    records: &AttestationRecords,
     signer: AccountId,
 ) -> bool {
-    let required_attestation_count = policy.required_schema_id.len();
-    if required_attestation_count == 0 {
-        return true;
+    match policy.to_expr() {
+        Some(expr) => expr.evaluate(&|req| holds_attestation(records, req, signer.clone())),
+        None => true,
     }
+}
 
-    let mut result = false;
-    for (i, &schema_id) in policy.required_schema_id.iter().enumerate() {
-        let has_attestation = get_user_attestations(user_a, schema_id, signer.clone()).len() > 0;
-
-        match policy.operator {
-            0x01 => {
-                if i == 0 {
-                    result = true;
-                }
-                result = result && has_attestation;
-                if !result {
-                    break;
-                }
-            }
-            0x02 => {
-                result = result || has_attestation;
-                if result {
-                    break;
-                }
-            }
-            0x03 => {
-                if i == 0 {
-                    result = true;
-                }
-                result = result && !has_attestation;
-                if !result {
-                    break;
-                }
-            }
-            _ => panic!("Unsupported operator"),
-        }
-    }
+fn holds_attestation(
+    records: &AttestationRecords,
+    req: &AttestationRequirement,
+    signer: AccountId,
+) -> bool {
+    let now = env::block_timestamp();
 
-    result
+    get_user_attestations(records.user_attestation, req.schema_id, signer)
+        .iter()
+        // Copies kept per user may be stale, check the current record
+        .map(|a| records.attestations.get(&a.attestation_id).unwrap_or(a))
+        // A superseded attestation is replaced by its amendment
+        .filter(|a| !records.superseded_by.contains_key(&a.attestation_id))
+        .any(|a| match records.schemas.get(&a.schema_id) {
+            Some(s) => a.satisfies(req, s, now),
+            None => false,
+        })
 }
 
 fn get_user_attestations(
@@ -201,6 +320,9 @@ mod tests {
             attestation_id: 0,                     // Default ID value
             creator: String::from("creator.test"), // The creator's address
             created_at: 0, // Default creation timestamp (you can set this to the current time if desired)
+            content_hash: vec![0; 32],
+            signed_at: 0,
+            public_key: vec![],
             signature: vec![0; 65], // Placeholder for the signature, e.g., 65 bytes for some types (e.g., ECDSA)
         };
         // Serialize the schema to message and sign it
@@ -213,4 +335,38 @@ mod tests {
         // Assert that the signature is valid
         assert!(success, "The signature should be valid.");
     }
+
+    #[test]
+    fn check_rejection() {
+        let signer = create_signer();
+
+        let rejection = ProofOfRejection {
+            attestation_id: 0,
+            creator: String::from("creator.test"),
+            content_hash: vec![1; 32],
+            reason_hash: vec![1; 32],
+            created_at: 0,
+            signature: vec![0; 64],
+        };
+        let message = rejection.to_ed25519_message();
+        let signature = sign_transaction(&message, &signer);
+
+        assert!(recover(signer.public, signature, &message));
+
+        // A rejection can't be replayed as a signature
+        let pos = ProofOfSignature {
+            attestation_id: 0,
+            creator: String::from("creator.test"),
+            created_at: 0,
+            content_hash: vec![1; 32],
+            signed_at: 0,
+            public_key: vec![],
+            signature: vec![0; 64],
+        };
+        assert!(!recover(
+            signer.public,
+            signature,
+            &pos.to_ed25519_message()
+        ));
+    }
 }
@@ -8,6 +8,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+/// Maximal nesting of a policy expression.
+pub const MAX_POLICY_DEPTH: usize = 8;
+/// Maximal number of nodes in a policy expression.
+pub const MAX_POLICY_NODES: usize = 64;
+
 /// ProofOfSignature struct representing the Proof-of-Signature parameters.
 // #[near_bindgen]
 #[derive(
@@ -34,6 +39,38 @@ pub struct SchemaMetadata {
     pub is_public: bool,
     pub is_revokable: bool,
     pub expire_in: u64,
+    #[serde(default, skip_serializing_if = "PoaMintPolicy::is_default")]
+    pub poa_mint_policy: PoaMintPolicy,
+}
+
+/// Receivers of the nfts minted once all signatures of an agreement are collected.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PoaMintPolicy {
+    /// One token per signatory.
+    Signatories,
+    /// One token to the attestation recipient.
+    Recipient,
+    /// A single token held by the app.
+    #[default]
+    App,
+}
+
+impl PoaMintPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == PoaMintPolicy::App
+    }
 }
 /// ProofOfSignature struct representing the Proof-of-Signature parameters.
 // #[near_bindgen]
@@ -49,9 +86,297 @@ pub struct SchemaMetadata {
     JsonSchema,
 )]
 pub struct SignatoryPolicy {
-    pub operator: u8, // bytes1 in Solidity can be represented as u8
+    #[schemars(with = "u8")]
+    pub operator: PolicyOperator, // encoded as the legacy operator byte
     pub signatory_description: String,
     pub required_schema_id: Vec<u128>, // uint256 is a large number, best suited to `u128` or `u256` library
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<PolicyExpr>, // replaces `operator` & `required_schema_id` when set
+}
+
+/// Operator applied to the `required_schema_id` of a SignatoryPolicy.
+/// Encoded as the legacy operator byte, in JSON and in Borsh alike.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum PolicyOperator {
+    /// 0x01: every required attestation is held.
+    And,
+    /// 0x02: at least one required attestation is held.
+    Or,
+    /// 0x03: none of the required attestations is held.
+    Not,
+}
+
+impl TryFrom<u8> for PolicyOperator {
+    type Error = String;
+
+    fn try_from(operator: u8) -> Result<Self, Self::Error> {
+        match operator {
+            0x01 => Ok(PolicyOperator::And),
+            0x02 => Ok(PolicyOperator::Or),
+            0x03 => Ok(PolicyOperator::Not),
+            _ => Err(format!("Unsupported operator {:#04x}", operator)),
+        }
+    }
+}
+
+impl From<PolicyOperator> for u8 {
+    fn from(operator: PolicyOperator) -> Self {
+        match operator {
+            PolicyOperator::And => 0x01,
+            PolicyOperator::Or => 0x02,
+            PolicyOperator::Not => 0x03,
+        }
+    }
+}
+
+impl BorshSerialize for PolicyOperator {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        u8::from(*self).serialize(writer)
+    }
+}
+
+impl BorshDeserialize for PolicyOperator {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let operator = u8::deserialize_reader(reader)?;
+        PolicyOperator::try_from(operator)
+            .map_err(|err| borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Boolean expression over the attestations held by a signatory.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyExpr {
+    /// Holds an attestation of the schema.
+    Schema(u128),
+    /// Holds an attestation of the schema matching the requirement.
+    Attestation(AttestationRequirement),
+    And(Vec<PolicyExpr>),
+    Or(Vec<PolicyExpr>),
+    Not(Box<PolicyExpr>),
+    /// At least `k` of the expressions hold.
+    Threshold {
+        k: u32,
+        of: Vec<PolicyExpr>,
+    },
+}
+
+/// Predicates over an attestation record required by a policy.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct AttestationRequirement {
+    pub schema_id: u128,
+    #[serde(default)]
+    pub not_revoked: bool,
+    #[serde(default)]
+    pub not_expired: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_creators: Vec<String>, // any creator if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldPredicate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>, // maximal time since the attestation was stored
+}
+
+/// Comparison of an attestation result value.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct FieldPredicate {
+    pub name: String,
+    pub op: CompareOp,
+    pub values: Vec<Vec<u8>>,
+}
+
+/// Operator of a FieldPredicate. Orderings compare values as big-endian unsigned integers,
+/// equality compares raw bytes.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+}
+
+impl AttestationRequirement {
+    /// Requirement of holding any attestation of the schema.
+    pub fn exists(schema_id: u128) -> Self {
+        AttestationRequirement {
+            schema_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) {
+        if let Some(max_age) = self.max_age {
+            assert!(max_age > 0, "invalid attestation max age!");
+        }
+
+        for field in &self.fields {
+            field.validate();
+        }
+    }
+}
+
+impl FieldPredicate {
+    pub fn validate(&self) {
+        let is_valid = match self.op {
+            CompareOp::In => !self.values.is_empty(),
+            _ => self.values.len() == 1,
+        };
+        assert!(is_valid, "invalid field predicate!");
+    }
+
+    /// Checks an attestation result value against the predicate.
+    pub fn matches(&self, value: &[u8]) -> bool {
+        let operand = self.values.first().map(Vec::as_slice).unwrap_or_default();
+        match self.op {
+            CompareOp::Eq => value == operand,
+            CompareOp::Ne => value != operand,
+            CompareOp::Gt => compare_uint(value, operand).is_gt(),
+            CompareOp::Gte => compare_uint(value, operand).is_ge(),
+            CompareOp::Lt => compare_uint(value, operand).is_lt(),
+            CompareOp::Lte => compare_uint(value, operand).is_le(),
+            CompareOp::In => self.values.iter().any(|v| v.as_slice() == value),
+        }
+    }
+}
+
+/// Compares big-endian unsigned integers of any length.
+fn compare_uint(a: &[u8], b: &[u8]) -> std::cmp::Ordering {
+    let a = &a[a.iter().position(|&byte| byte != 0).unwrap_or(a.len())..];
+    let b = &b[b.iter().position(|&byte| byte != 0).unwrap_or(b.len())..];
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+impl PolicyExpr {
+    /// Evaluates the expression, `holds` tells whether the signatory holds an attestation matching a requirement.
+    pub fn evaluate(&self, holds: &dyn Fn(&AttestationRequirement) -> bool) -> bool {
+        match self {
+            PolicyExpr::Schema(schema_id) => holds(&AttestationRequirement::exists(*schema_id)),
+            PolicyExpr::Attestation(requirement) => holds(requirement),
+            PolicyExpr::And(exprs) => exprs.iter().all(|e| e.evaluate(holds)),
+            PolicyExpr::Or(exprs) => exprs.iter().any(|e| e.evaluate(holds)),
+            PolicyExpr::Not(expr) => !expr.evaluate(holds),
+            PolicyExpr::Threshold { k, of } => {
+                of.iter().filter(|e| e.evaluate(holds)).count() >= *k as usize
+            }
+        }
+    }
+
+    pub fn validate(&self) {
+        let mut nodes = 0;
+        self.validate_node(1, &mut nodes);
+    }
+
+    fn validate_node(&self, depth: usize, nodes: &mut usize) {
+        assert!(depth <= MAX_POLICY_DEPTH, "policy expression too deep!");
+
+        *nodes += 1;
+        assert!(*nodes <= MAX_POLICY_NODES, "policy expression too large!");
+
+        match self {
+            PolicyExpr::Schema(_) => {}
+            PolicyExpr::Attestation(requirement) => requirement.validate(),
+            PolicyExpr::And(exprs) | PolicyExpr::Or(exprs) => {
+                assert!(!exprs.is_empty(), "empty policy expression!");
+                for expr in exprs {
+                    expr.validate_node(depth + 1, nodes);
+                }
+            }
+            PolicyExpr::Not(expr) => expr.validate_node(depth + 1, nodes),
+            PolicyExpr::Threshold { k, of } => {
+                assert!(
+                    *k >= 1 && *k as usize <= of.len(),
+                    "invalid policy threshold!"
+                );
+                for expr in of {
+                    expr.validate_node(depth + 1, nodes);
+                }
+            }
+        }
+    }
+}
+
+impl SignatoryPolicy {
+    /// Returns the policy as an expression, lifting the legacy operator over `required_schema_id`.
+    /// `None` means the policy has no requirements.
+    pub fn to_expr(&self) -> Option<PolicyExpr> {
+        if let Some(expr) = &self.expression {
+            return Some(expr.clone());
+        }
+
+        if self.required_schema_id.is_empty() {
+            return None;
+        }
+
+        let required = self
+            .required_schema_id
+            .iter()
+            .map(|id| PolicyExpr::Schema(*id));
+        let expr = match self.operator {
+            PolicyOperator::And => PolicyExpr::And(required.collect()),
+            PolicyOperator::Or => PolicyExpr::Or(required.collect()),
+            PolicyOperator::Not => {
+                PolicyExpr::And(required.map(|e| PolicyExpr::Not(Box::new(e))).collect())
+            }
+        };
+        Some(expr)
+    }
+
+    pub fn validate(&self) {
+        if let Some(expr) = &self.expression {
+            assert!(
+                self.required_schema_id.is_empty(),
+                "ambiguous signatory policy!"
+            );
+            expr.validate();
+        }
+    }
 }
 
 /// ProofOfSignature struct representing the Proof-of-Signature parameters.
@@ -70,6 +395,39 @@ pub struct SignatoryPolicy {
 pub struct SchemaDefinition {
     pub definition_type: String,
     pub definition_name: String,
+    #[serde(default, skip_serializing_if = "FieldPrivacy::is_default")]
+    pub privacy: FieldPrivacy,
+}
+
+/// How the attestation values of a schema field are kept on chain.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldPrivacy {
+    /// The value is stored as is.
+    #[default]
+    Plaintext,
+    /// The value is the salted hash `sha256(salt || value)`, salt and value are revealed off-chain.
+    HashCommitment,
+    /// The value is encrypted to the keys of the recipient and the signatories.
+    Encrypted,
+}
+
+impl FieldPrivacy {
+    pub fn is_default(&self) -> bool {
+        *self == FieldPrivacy::Plaintext
+    }
 }
 
 #[derive(
@@ -91,6 +449,7 @@ pub struct Schema {
     pub signature: Vec<u8>,
 }
 
+/// Signed content of a schema. The contract assigns the id on storage, so it is left out.
 #[derive(
     BorshDeserialize,
     BorshSerialize,
@@ -103,7 +462,6 @@ pub struct Schema {
     JsonSchema,
 )]
 pub struct SchemaMessage {
-    schema_id: u128, // Assuming large ids
     metadata: SchemaMetadata,
     signatory_policy: Vec<SignatoryPolicy>,
     schema_definition: Vec<SchemaDefinition>,
@@ -112,7 +470,6 @@ pub struct SchemaMessage {
 impl Schema {
     pub fn to_ed25519_message(&self) -> Vec<u8> {
         let schema = SchemaMessage {
-            schema_id: self.schema_id,
             metadata: self.metadata.clone(),
             signatory_policy: self.signatory_policy.clone(),
             schema_definition: self.schema_definition.clone(),
@@ -136,6 +493,11 @@ impl Schema {
             "empty schema definition!"
         );
 
+        // Reject malformed policies before any signatory relies on them
+        for policy in &self.signatory_policy {
+            policy.validate();
+        }
+
         let signature = Signature::from_bytes(&self.signature).expect("Invalid signature");
 
         println!("{:?}", env::signer_account_pk());
@@ -196,15 +558,18 @@ mod tests {
                 is_public: false,
                 is_revokable: true,
                 expire_in: 0,
+                poa_mint_policy: PoaMintPolicy::App,
             },
             signatory_policy: vec![SignatoryPolicy {
-                operator: 0x01, // For example, "AND" operation
+                operator: PolicyOperator::And,
                 signatory_description: String::from("Main Signatory"),
                 required_schema_id: vec![0],
+                expression: None,
             }],
             schema_definition: vec![SchemaDefinition {
                 definition_type: String::from("string"),
                 definition_name: String::from("vacancies"),
+                privacy: FieldPrivacy::Plaintext,
             }],
             //TODO: set default Signature instance
             signature: vec![0; 65],
@@ -220,4 +585,142 @@ mod tests {
         // Assert that the signature is valid
         assert!(success, "The signature should be valid.");
     }
+
+    #[test]
+    fn test_legacy_policy_encoding() {
+        let legacy =
+            r#"{"operator":3,"signatory_description":"Employee","required_schema_id":[1,2]}"#;
+
+        let policy: SignatoryPolicy = serde_json::from_str(legacy).unwrap();
+        assert_eq!(policy.operator, PolicyOperator::Not);
+        assert_eq!(policy.expression, None);
+
+        // Re-encoding keeps signed legacy messages intact
+        assert_eq!(serde_json::to_string(&policy).unwrap(), legacy);
+
+        assert_eq!(
+            policy.to_expr(),
+            Some(PolicyExpr::And(vec![
+                PolicyExpr::Not(Box::new(PolicyExpr::Schema(1))),
+                PolicyExpr::Not(Box::new(PolicyExpr::Schema(2))),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_legacy_policy_borsh() {
+        for (operator, byte) in [
+            (PolicyOperator::And, 0x01),
+            (PolicyOperator::Or, 0x02),
+            (PolicyOperator::Not, 0x03),
+        ] {
+            assert_eq!(borsh::to_vec(&operator).unwrap(), vec![byte]);
+            assert_eq!(PolicyOperator::try_from_slice(&[byte]).unwrap(), operator);
+        }
+        assert!(PolicyOperator::try_from_slice(&[0x04]).is_err());
+
+        // Policies stored with the u8 operator keep their layout
+        let mut legacy = vec![0x02];
+        legacy.extend(borsh::to_vec(&String::from("Employee")).unwrap());
+        legacy.extend(borsh::to_vec(&vec![1u128, 2]).unwrap());
+
+        let policy = SignatoryPolicy {
+            operator: PolicyOperator::Or,
+            signatory_description: String::from("Employee"),
+            required_schema_id: vec![1, 2],
+            expression: None,
+        };
+        let encoded = borsh::to_vec(&policy).unwrap();
+        assert_eq!(encoded[..legacy.len()], legacy[..]);
+        assert_eq!(SignatoryPolicy::try_from_slice(&encoded).unwrap(), policy);
+    }
+
+    #[test]
+    fn test_unsupported_operator() {
+        let policy = r#"{"operator":4,"signatory_description":"role","required_schema_id":[0]}"#;
+
+        let err = serde_json::from_str::<SignatoryPolicy>(policy).unwrap_err();
+        assert!(err.to_string().contains("Unsupported operator"));
+    }
+
+    #[test]
+    fn test_policy_expr() {
+        // (0 AND NOT 1) OR 2 of [3, 4, 5]
+        let expr = PolicyExpr::Or(vec![
+            PolicyExpr::And(vec![
+                PolicyExpr::Schema(0),
+                PolicyExpr::Not(Box::new(PolicyExpr::Schema(1))),
+            ]),
+            PolicyExpr::Threshold {
+                k: 2,
+                of: vec![
+                    PolicyExpr::Schema(3),
+                    PolicyExpr::Schema(4),
+                    PolicyExpr::Schema(5),
+                ],
+            },
+        ]);
+        expr.validate();
+
+        assert!(expr.evaluate(&|r| r.schema_id == 0));
+        assert!(!expr.evaluate(&|r| r.schema_id == 0 || r.schema_id == 1));
+        assert!(expr.evaluate(&|r| [1, 3, 5].contains(&r.schema_id)));
+        assert!(!expr.evaluate(&|r| r.schema_id == 4));
+
+        let json = serde_json::to_string(&expr).unwrap();
+        assert_eq!(serde_json::from_str::<PolicyExpr>(&json).unwrap(), expr);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid policy threshold!")]
+    fn test_invalid_policy_threshold() {
+        PolicyExpr::Threshold {
+            k: 3,
+            of: vec![PolicyExpr::Schema(0), PolicyExpr::Schema(1)],
+        }
+        .validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "policy expression too deep!")]
+    fn test_policy_too_deep() {
+        let mut expr = PolicyExpr::Schema(0);
+        for _ in 0..MAX_POLICY_DEPTH {
+            expr = PolicyExpr::Not(Box::new(expr));
+        }
+        expr.validate();
+    }
+
+    #[test]
+    fn test_field_predicate() {
+        let age_over_18 = FieldPredicate {
+            name: String::from("age"),
+            op: CompareOp::Gte,
+            values: vec![vec![18]],
+        };
+        age_over_18.validate();
+        assert!(age_over_18.matches(&[18]));
+        assert!(age_over_18.matches(&[0, 0, 1, 0]));
+        assert!(!age_over_18.matches(&[0, 17]));
+
+        let country = FieldPredicate {
+            name: String::from("country"),
+            op: CompareOp::In,
+            values: vec![b"UA".to_vec(), b"PL".to_vec()],
+        };
+        country.validate();
+        assert!(country.matches(b"PL"));
+        assert!(!country.matches(b"US"));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid field predicate!")]
+    fn test_invalid_field_predicate() {
+        FieldPredicate {
+            name: String::from("age"),
+            op: CompareOp::Gte,
+            values: vec![],
+        }
+        .validate();
+    }
 }
@@ -0,0 +1,59 @@
+//! Off-chain verification of DAOsign records.
+//!
+//! The signature check is `no_std` and depends on ed25519-dalek only. The record checks rebuild
+//! messages with the same `to_ed25519_message` the contract uses, and the record crates build on
+//! near-sdk, so they need the default `std` feature. Signed messages leave out the ids the
+//! contract assigns, so records fetched from chain verify with their stored ids. Public keys are
+//! raw 32 byte ed25519 keys, e.g. the access keys of the signer accounts.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+
+#[cfg(feature = "std")]
+mod records;
+
+#[cfg(feature = "std")]
+pub use records::*;
+
+/// Reason a record failed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    InvalidPublicKey,
+    InvalidSignature,
+    /// The proof refers to another attestation.
+    AttestationMismatch,
+    /// The proof was signed over other attestation content.
+    ContentHashMismatch,
+    /// The signer is not a signatory of the attestation.
+    UnknownSignatory(String),
+    /// No public key is known for the signer, or it differs from the one in the proof.
+    UnknownSignerKey(String),
+    DuplicateSigner(String),
+    /// The signers don't reach the signing threshold of the attestation.
+    ThresholdNotReached,
+    /// The revealed value doesn't match the commitment of the field.
+    CommitmentMismatch(String),
+    /// The attestation is not under the root of the bulk attestation.
+    BulkInclusionMismatch,
+    /// The disclosed field is not under the fields root of the attestation.
+    DisclosureMismatch(String),
+}
+
+/// Verifies an ed25519 `signature` of `message` made with `public_key`.
+pub fn verify_signature(
+    public_key: &[u8],
+    signature: &[u8],
+    message: &[u8],
+) -> Result<(), VerifyError> {
+    let public_key =
+        PublicKey::from_bytes(public_key).map_err(|_| VerifyError::InvalidPublicKey)?;
+    let signature = Signature::from_bytes(signature).map_err(|_| VerifyError::InvalidSignature)?;
+
+    public_key
+        .verify(message, &signature)
+        .map_err(|_| VerifyError::InvalidSignature)
+}
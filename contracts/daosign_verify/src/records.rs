@@ -0,0 +1,480 @@
+use std::collections::BTreeMap;
+
+use daosign_attestation::{disclosure::FieldDisclosure, Attestation, BulkAttestation};
+use daosign_proof_of_agreement::ProofOfAgreement;
+use daosign_proof_of_signature::ProofOfSignature;
+use daosign_schema::Schema;
+
+use crate::{verify_signature, VerifyError};
+
+/// Verifies the signature of the schema creator.
+pub fn verify_schema(s: &Schema, public_key: &[u8]) -> Result<(), VerifyError> {
+    verify_signature(public_key, &s.signature, &s.to_ed25519_message())
+}
+
+/// Verifies the signature of the attestation creator.
+pub fn verify_attestation(a: &Attestation, public_key: &[u8]) -> Result<(), VerifyError> {
+    verify_signature(public_key, &a.signature, &a.to_ed25519_message())
+}
+
+/// Verifies an attestation claimed from `bulk`: the root is signed by its creator with `public_key`
+/// and `proof` leads from `a` to it. Claimed attestations carry no signature of their own.
+pub fn verify_bulk_claim(
+    a: &Attestation,
+    bulk: &BulkAttestation,
+    proof: &[Vec<u8>],
+    public_key: &[u8],
+) -> Result<(), VerifyError> {
+    verify_signature(public_key, &bulk.signature, &bulk.to_ed25519_message())?;
+    if !bulk.includes(a, proof) {
+        return Err(VerifyError::BulkInclusionMismatch);
+    }
+    Ok(())
+}
+
+/// Verifies a Proof of Signature of `a` made with `public_key`.
+pub fn verify_pos(
+    pos: &ProofOfSignature,
+    a: &Attestation,
+    public_key: &[u8],
+) -> Result<(), VerifyError> {
+    if pos.attestation_id != a.attestation_id {
+        return Err(VerifyError::AttestationMismatch);
+    }
+    if pos.content_hash != a.content_hash() {
+        return Err(VerifyError::ContentHashMismatch);
+    }
+    if !a.signatories.contains(&pos.creator) {
+        return Err(VerifyError::UnknownSignatory(pos.creator.clone()));
+    }
+    verify_signature(public_key, &pos.signature, &pos.to_ed25519_message())
+}
+
+/// Verifies a Proof of Agreement of `a`. Every signer must be a signatory whose key is listed in
+/// `public_keys` (account id => public key), and together they must reach the signing threshold.
+pub fn verify_poa(
+    poa: &ProofOfAgreement,
+    a: &Attestation,
+    public_keys: &BTreeMap<String, Vec<u8>>,
+) -> Result<(), VerifyError> {
+    if poa.attestation_id != a.attestation_id {
+        return Err(VerifyError::AttestationMismatch);
+    }
+    if poa.content_hash != a.content_hash() {
+        return Err(VerifyError::ContentHashMismatch);
+    }
+
+    let mut signers: Vec<String> = Vec::new();
+    for s in &poa.signatures {
+        if !a.signatories.contains(&s.account_id) {
+            return Err(VerifyError::UnknownSignatory(s.account_id.clone()));
+        }
+        if signers.contains(&s.account_id) {
+            return Err(VerifyError::DuplicateSigner(s.account_id.clone()));
+        }
+        if public_keys.get(&s.account_id) != Some(&s.public_key) {
+            return Err(VerifyError::UnknownSignerKey(s.account_id.clone()));
+        }
+        verify_signature(
+            &s.public_key,
+            &s.signature,
+            &s.to_ed25519_message(poa.attestation_id, &poa.content_hash),
+        )?;
+        signers.push(s.account_id.clone());
+    }
+
+    if !a.is_threshold_reached(&signers) {
+        return Err(VerifyError::ThresholdNotReached);
+    }
+    Ok(())
+}
+
+/// Verifies a value revealed off-chain against the commitment of a `HashCommitment` field of `a`.
+pub fn verify_field_reveal(
+    a: &Attestation,
+    s: &Schema,
+    name: &str,
+    salt: &[u8],
+    value: &[u8],
+) -> Result<(), VerifyError> {
+    if a.verify_field_reveal(s, name, salt, value) {
+        Ok(())
+    } else {
+        Err(VerifyError::CommitmentMismatch(String::from(name)))
+    }
+}
+
+/// Verifies a field disclosed by the holder of `a` against its `fields_root`.
+pub fn verify_field_disclosure(
+    a: &Attestation,
+    disclosure: &FieldDisclosure,
+) -> Result<(), VerifyError> {
+    if a.verify_field_disclosure(disclosure) {
+        Ok(())
+    } else {
+        Err(VerifyError::DisclosureMismatch(disclosure.name.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use daosign_attestation::AttestationResult;
+    use daosign_proof_of_agreement::AgreementSignature;
+    use daosign_schema::{FieldPrivacy, PoaMintPolicy, SchemaDefinition, SchemaMetadata};
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    fn create_signer() -> Keypair {
+        let mut csprng = OsRng {};
+        Keypair::generate(&mut csprng)
+    }
+
+    fn public_key(signer: &Keypair) -> Vec<u8> {
+        signer.public.to_bytes().to_vec()
+    }
+
+    fn create_schema() -> Schema {
+        Schema {
+            schema_id: 0,
+            metadata: SchemaMetadata {
+                name: "daosign_employment".to_string(),
+                description: "Employment agreement".to_string(),
+                attestation_type: "agreement".to_string(),
+                nft_name: "employment collection".to_string(),
+                nft_symbol: "emp".to_string(),
+                collection_id: String::new(),
+                creator: "employer.testnet".to_string(),
+                created_at: 1,
+                is_nft: false,
+                is_public: false,
+                is_revokable: true,
+                expire_in: 0,
+                poa_mint_policy: PoaMintPolicy::App,
+            },
+            signatory_policy: vec![],
+            schema_definition: vec![SchemaDefinition {
+                definition_type: "uint256".to_string(),
+                definition_name: "salary".to_string(),
+                privacy: FieldPrivacy::Plaintext,
+            }],
+            signature: vec![],
+        }
+    }
+
+    fn create_attestation() -> Attestation {
+        Attestation {
+            attestation_id: 0,
+            schema_id: 0,
+            attestation_result: vec![AttestationResult {
+                attestation_result_type: "uint256".to_string(),
+                name: "salary".to_string(),
+                value: vec![16, 0],
+            }],
+            creator: "employer.testnet".to_string(),
+            recipient: "employee.testnet".to_string(),
+            created_at: 1,
+            signatories: vec![
+                "employee.testnet".to_string(),
+                "employer.testnet".to_string(),
+            ],
+            signature: vec![],
+            is_revoked: false,
+            revoked_at: 0,
+            revoke_signature: vec![],
+            signatory_roles: vec![],
+            threshold: None,
+            signing_order: None,
+            sign_deadline: None,
+            is_cancelled: false,
+            cancelled_at: 0,
+            cancel_signature: vec![],
+            documents: vec![],
+            fields_root: vec![],
+            refers_to: None,
+            supersedes: None,
+            stored_at: 0,
+        }
+    }
+
+    fn sign_pos(a: &Attestation, account_id: &str, signer: &Keypair) -> ProofOfSignature {
+        let mut pos = ProofOfSignature {
+            attestation_id: a.attestation_id,
+            creator: account_id.to_string(),
+            created_at: 20,
+            content_hash: a.content_hash(),
+            signed_at: 10,
+            public_key: public_key(signer),
+            signature: vec![],
+        };
+        pos.signature = signer.sign(&pos.to_ed25519_message()).to_bytes().to_vec();
+        pos
+    }
+
+    fn agreement_signature(pos: &ProofOfSignature) -> AgreementSignature {
+        AgreementSignature {
+            account_id: pos.creator.clone(),
+            public_key: pos.public_key.clone(),
+            signature: pos.signature.clone(),
+            signed_at: pos.signed_at,
+        }
+    }
+
+    #[test]
+    fn check_schema_and_attestation() {
+        let signer = create_signer();
+
+        let mut schema = create_schema();
+        schema.signature = signer
+            .sign(&schema.to_ed25519_message())
+            .to_bytes()
+            .to_vec();
+        assert_eq!(verify_schema(&schema, &public_key(&signer)), Ok(()));
+        assert_eq!(
+            verify_schema(&schema, &public_key(&create_signer())),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        let mut attestation = create_attestation();
+        attestation.signature = signer
+            .sign(&attestation.to_ed25519_message())
+            .to_bytes()
+            .to_vec();
+        assert_eq!(
+            verify_attestation(&attestation, &public_key(&signer)),
+            Ok(())
+        );
+
+        attestation.attestation_result[0].value = vec![32, 0];
+        assert_eq!(
+            verify_attestation(&attestation, &public_key(&signer)),
+            Err(VerifyError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_attestation(&attestation, &[0; 3]),
+            Err(VerifyError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn check_pos() {
+        let signer = create_signer();
+        let attestation = create_attestation();
+
+        let pos = sign_pos(&attestation, "employee.testnet", &signer);
+        assert_eq!(verify_pos(&pos, &attestation, &public_key(&signer)), Ok(()));
+
+        let mut changed = attestation.clone();
+        changed.attestation_result[0].value = vec![32, 0];
+        assert_eq!(
+            verify_pos(&pos, &changed, &public_key(&signer)),
+            Err(VerifyError::ContentHashMismatch)
+        );
+
+        let stranger = sign_pos(&attestation, "stranger.testnet", &signer);
+        assert_eq!(
+            verify_pos(&stranger, &attestation, &public_key(&signer)),
+            Err(VerifyError::UnknownSignatory(
+                "stranger.testnet".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn check_stored_ids() {
+        let signer = create_signer();
+
+        // Clients sign before the contract assigns the ids
+        let mut schema = create_schema();
+        schema.signature = signer
+            .sign(&schema.to_ed25519_message())
+            .to_bytes()
+            .to_vec();
+        let mut attestation = create_attestation();
+        attestation.signature = signer
+            .sign(&attestation.to_ed25519_message())
+            .to_bytes()
+            .to_vec();
+
+        schema.schema_id = 7;
+        attestation.schema_id = 7;
+        attestation.attestation_id = 3;
+        // The schema id of the attestation is signed
+        assert_eq!(
+            verify_attestation(&attestation, &public_key(&signer)),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        attestation.schema_id = 0;
+        assert_eq!(verify_schema(&schema, &public_key(&signer)), Ok(()));
+        assert_eq!(
+            verify_attestation(&attestation, &public_key(&signer)),
+            Ok(())
+        );
+
+        let pos = sign_pos(&attestation, "employee.testnet", &signer);
+        assert_eq!(pos.attestation_id, 3);
+        assert_eq!(verify_pos(&pos, &attestation, &public_key(&signer)), Ok(()));
+    }
+
+    #[test]
+    fn check_bulk_claim() {
+        use daosign_attestation::merkle;
+
+        let signer = create_signer();
+        let attestations: Vec<Attestation> = (0..3)
+            .map(|i| {
+                let mut a = create_attestation();
+                a.attestation_result[0].value = vec![16, i];
+                a
+            })
+            .collect();
+        let leaves: Vec<Vec<u8>> = attestations.iter().map(|a| a.leaf_hash()).collect();
+
+        let mut bulk = BulkAttestation {
+            bulk_id: 2,
+            schema_id: 0,
+            creator: "employer.testnet".to_string(),
+            merkle_root: merkle::root(&leaves),
+            leaves: 3,
+            created_at: 1,
+            signature: vec![],
+        };
+        bulk.signature = signer.sign(&bulk.to_ed25519_message()).to_bytes().to_vec();
+
+        // As stored once claimed
+        let mut claimed = attestations[1].clone();
+        claimed.attestation_id = 5;
+        assert_eq!(
+            verify_bulk_claim(
+                &claimed,
+                &bulk,
+                &merkle::proof(&leaves, 1),
+                &public_key(&signer)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            verify_bulk_claim(
+                &claimed,
+                &bulk,
+                &merkle::proof(&leaves, 0),
+                &public_key(&signer)
+            ),
+            Err(VerifyError::BulkInclusionMismatch)
+        );
+        assert_eq!(
+            verify_bulk_claim(
+                &claimed,
+                &bulk,
+                &merkle::proof(&leaves, 1),
+                &public_key(&create_signer())
+            ),
+            Err(VerifyError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn check_field_reveal() {
+        let mut schema = create_schema();
+        schema.schema_definition[0].privacy = FieldPrivacy::HashCommitment;
+        let salt = [7; 32];
+        let mut attestation = create_attestation();
+        attestation.attestation_result[0].value =
+            daosign_attestation::field_commitment(&salt, &[16, 0]);
+
+        assert_eq!(
+            verify_field_reveal(&attestation, &schema, "salary", &salt, &[16, 0]),
+            Ok(())
+        );
+        assert_eq!(
+            verify_field_reveal(&attestation, &schema, "salary", &salt, &[32, 0]),
+            Err(VerifyError::CommitmentMismatch("salary".to_string()))
+        );
+    }
+
+    #[test]
+    fn check_field_disclosure() {
+        use daosign_attestation::disclosure::{disclose, fields_root, SaltedField};
+
+        let fields = vec![
+            SaltedField {
+                name: "employer".to_string(),
+                value: b"employer.testnet".to_vec(),
+                salt: vec![1; 32],
+            },
+            SaltedField {
+                name: "salary".to_string(),
+                value: vec![16, 0],
+                salt: vec![2; 32],
+            },
+        ];
+        let mut attestation = create_attestation();
+        attestation.attestation_result = fields
+            .iter()
+            .map(|f| AttestationResult {
+                attestation_result_type: "string".to_string(),
+                name: f.name.clone(),
+                value: f.commitment(),
+            })
+            .collect();
+        attestation.fields_root = fields_root(&fields);
+
+        let mut disclosure = disclose(&fields, "employer").unwrap();
+        assert_eq!(verify_field_disclosure(&attestation, &disclosure), Ok(()));
+
+        disclosure.value = b"stranger.testnet".to_vec();
+        assert_eq!(
+            verify_field_disclosure(&attestation, &disclosure),
+            Err(VerifyError::DisclosureMismatch("employer".to_string()))
+        );
+    }
+
+    #[test]
+    fn check_poa() {
+        let employee = create_signer();
+        let employer = create_signer();
+        let attestation = create_attestation();
+
+        let employee_pos = sign_pos(&attestation, "employee.testnet", &employee);
+        let employer_pos = sign_pos(&attestation, "employer.testnet", &employer);
+
+        let mut public_keys = BTreeMap::new();
+        public_keys.insert("employee.testnet".to_string(), public_key(&employee));
+        public_keys.insert("employer.testnet".to_string(), public_key(&employer));
+
+        let mut poa = ProofOfAgreement {
+            attestation_id: 0,
+            content_hash: attestation.content_hash(),
+            completed_at: 100,
+            signatures: vec![
+                agreement_signature(&employee_pos),
+                agreement_signature(&employer_pos),
+            ],
+        };
+        assert_eq!(verify_poa(&poa, &attestation, &public_keys), Ok(()));
+
+        // A key the account doesn't hold
+        public_keys.insert("employer.testnet".to_string(), public_key(&create_signer()));
+        assert_eq!(
+            verify_poa(&poa, &attestation, &public_keys),
+            Err(VerifyError::UnknownSignerKey(
+                "employer.testnet".to_string()
+            ))
+        );
+        public_keys.insert("employer.testnet".to_string(), public_key(&employer));
+
+        // Every signatory must sign
+        poa.signatures.pop();
+        assert_eq!(
+            verify_poa(&poa, &attestation, &public_keys),
+            Err(VerifyError::ThresholdNotReached)
+        );
+
+        poa.signatures.push(agreement_signature(&employee_pos));
+        assert_eq!(
+            verify_poa(&poa, &attestation, &public_keys),
+            Err(VerifyError::DuplicateSigner("employee.testnet".to_string()))
+        );
+    }
+}
@@ -45,7 +45,7 @@ async function CREATE_SCHEMA() {
         data: attestation,
       },
       100000000000000, // Gas (100 Tgas)
-      depositYocto // No deposit needed
+      depositYocto // Covers the storage and nft mints, the excess is refunded
     );
 
     // Send transaction
@@ -95,8 +95,8 @@ function get_default_attestation(creator, signatory) {
     revoke_signature: new Array(65).fill(0), // Placeholder revoke signature
   };
 
+  // The contract assigns `attestation_id`, the signed message leaves it out
   const attestation_message = {
-    attestation_id: 0,
     schema_id: 0,
     attestation_result: [
       {
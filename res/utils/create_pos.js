@@ -1,11 +1,13 @@
 require("dotenv").config();
 const nearAPI = require("near-api-js");
+const crypto = require("crypto");
 
 const { connect, keyStores, utils, transactions } = nearAPI;
 // Load environment variables
 const ACCOUNT_ID = process.env.NEAR_ACCOUNT_ID;
 const PRIVATE_KEY = process.env.PRIVATE_KEY;
 const CONTRACT_ID = process.env.APP_CONTRACT_ID;
+const ATTESTATION_ID = 0;
 
 async function CREATE_SCHEMA() {
   try {
@@ -31,14 +33,25 @@ async function CREATE_SCHEMA() {
     // Load account
     const account = await near.account(ACCOUNT_ID);
 
-    let message = get_message(); // Pass creator
+    // The signature commits to the stored attestation content
+    const attestation = await account.viewFunction({
+      contractId: CONTRACT_ID,
+      methodName: "get_attestation",
+      args: { attestation_id: ATTESTATION_ID },
+    });
+    const contentHash = contentHashOf(attestation);
+    const signedAt = (Date.now() - 60000) * 1000000; // nanoseconds, a minute behind the chain
+
+    let message = get_message(contentHash, signedAt); // Pass creator
 
     signature = signMessage(message, keyPair);
 
     const data = {
-      attestation_id: 0,
+      attestation_id: ATTESTATION_ID,
       creator: ACCOUNT_ID,
       created_at: 0,
+      content_hash: contentHash,
+      signed_at: signedAt,
       signature: signature,
     };
 
@@ -52,7 +65,7 @@ async function CREATE_SCHEMA() {
         data: data,
       },
       100000000000000, // Gas (100 Tgas)
-      depositYocto // No deposit needed
+      depositYocto // Covers the storage and nft mints, the excess is refunded
     );
 
     // Send transaction
@@ -76,12 +89,42 @@ function signMessage(message, keyPair) {
   return Array.from(signature); // Convert Buffer to an array of numbers
 }
 
-function get_message(signatory) {
-  // Create the Attestation object
+function get_message(contentHash, signedAt) {
+  // Create the ProofOfSignature message
   return {
-    attestation_id: 0,
+    attestation_id: ATTESTATION_ID,
     creator: ACCOUNT_ID,
+    content_hash: contentHash,
+    signed_at: signedAt,
+  };
+}
+
+// sha256 of the attestation message, fields in the order the contract serializes them
+function contentHashOf(a) {
+  const message = {
+    schema_id: a.schema_id,
+    attestation_result: a.attestation_result,
+    creator: a.creator,
+    recipient: a.recipient,
+    created_at: a.created_at,
+    signatories: a.signatories,
   };
+  // Optional keys are left out when unset, like the contract does
+  const optional = [
+    "signatory_roles",
+    "threshold",
+    "signing_order",
+    "sign_deadline",
+    "documents",
+    "fields_root",
+    "refers_to",
+    "supersedes",
+  ];
+  for (const key of optional) {
+    if (a[key] !== undefined) message[key] = a[key];
+  }
+  const digest = crypto.createHash("sha256").update(Buffer.from(JSON.stringify(message))).digest();
+  return Array.from(digest);
 }
 
 function publicKeyToBytes(publicKey) {
@@ -6,7 +6,6 @@ const { connect, keyStores, transactions } = nearAPI;
 const ACCOUNT_ID = process.env.NEAR_ACCOUNT_ID;
 const PRIVATE_KEY = process.env.PRIVATE_KEY;
 const CONTRACT_ID = process.env.APP_CONTRACT_ID;
-const NFT_ACCOUNT_ID = process.env.NFT_ACCOUNT_ID;
 
 async function CREATE_SCHEMA() {
   try {
@@ -32,9 +31,12 @@ async function CREATE_SCHEMA() {
     // Load account
     const account = await near.account(ACCOUNT_ID);
 
+    // The app names the collection of nft schemas, `collection_id` is left empty
     let schema = get_default_schema(pk); // Pass creator
 
-    schema.signature = signMessage(schema, keyPair);
+    // The contract assigns `schema_id`, the signed message leaves it out
+    const { schema_id, ...message } = schema;
+    schema.signature = signMessage(message, keyPair);
 
     // Define the function call transaction
     const functionCall = transactions.functionCall(
@@ -42,8 +44,8 @@ async function CREATE_SCHEMA() {
       {
         data: schema,
       },
-      100000000000000, // Gas (100 Tgas)
-      "0" // No deposit needed
+      200000000000000, // Gas (200 Tgas), nft schemas deploy a collection
+      nearAPI.utils.format.parseNearAmount(process.env.DEPOSIT_AMOUNT || "4") // Covers the collection deployment
     );
 
     // Send transaction
@@ -76,7 +78,7 @@ function get_default_schema(creator) {
       attestation_type: "agreement",
       nft_name: "nft_name",
       nft_symbol: "nft_symbol",
-      collection_id: NFT_ACCOUNT_ID,
+      collection_id: "",
       creator: ACCOUNT_ID,
       created_at: 1,
       is_nft: true,